@@ -0,0 +1,100 @@
+// Benchmarks for whole solves, for each rule on its own, and for parsing.
+//
+// Run them with `cargo bench`. Criterion keeps the results in
+// target/criterion, and compares each run with the one before, so a slower
+// solve is reported as a regression. To compare with a fixed point instead:
+//   cargo bench -- --save-baseline before
+//   (make changes)
+//   cargo bench -- --baseline before
+
+use std::fs;
+use std::path::Path;
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+use communicator::*;
+
+fn reference_text(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("puzzles").join(format!("{}.txt", name));
+
+    fs::read_to_string(&path).unwrap_or_else(|e| panic!("Unable to read {}: {}", path.display(), e))
+}
+
+fn reference(name: &str) -> Board {
+    parse_board(&reference_text(name)).unwrap_or_else(|e| panic!("Unable to parse {}: {}", name, e))
+}
+
+// The rules solve these on their own. puzzle4 is 15x15.
+const RULES_SOLVE: [(&str, &str); 3] = [
+    ("6x6",   "generated-6x6-seed0"),
+    ("10x10", "generated-10x10-seed0"),
+    ("15x15", "puzzle4"),
+];
+
+// The rules get stuck on these, and a search finishes them off
+const SEARCH_SOLVES: [(&str, &str); 3] = [
+    ("6x6",   "generated-6x6-seed1"),
+    ("10x10", "generated-10x10-seed2"),
+    ("15x15", "generated-15x15-seed10"),
+];
+
+fn bench_solves(c: &mut Criterion) {
+    let mut group = c.benchmark_group("solve");
+    for &(size, name) in RULES_SOLVE.iter() {
+        let board = reference(name);
+        group.bench_function(size, |b| b.iter_batched(
+            || board.clone(),
+            |mut board| assert!(solve(&mut board, &mut Quiet).unwrap()),
+            BatchSize::SmallInput));
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("solve_with_search");
+    for &(size, name) in SEARCH_SOLVES.iter() {
+        let board = reference(name);
+        group.bench_function(size, |b| b.iter_batched(
+            || board.clone(),
+            |mut board| assert!(solve_with_search(&mut board, &mut Quiet).unwrap()),
+            BatchSize::SmallInput));
+    }
+    group.finish();
+
+    // Probing runs the cheap rules on a hypothetical board for each assumption
+    let mut group = c.benchmark_group("solve_with_probing");
+    for &(size, name) in SEARCH_SOLVES.iter() {
+        let board = reference(name);
+        group.bench_function(size, |b| b.iter_batched(
+            || board.clone(),
+            |mut board| solve_with_probing(&mut board, 2, &mut Quiet).unwrap(),
+            BatchSize::SmallInput));
+    }
+    group.finish();
+}
+
+// Each rule gets a board the rules are stuck on, so it has to look over the
+// whole board, and finds nothing to do
+fn bench_rules(c: &mut Criterion) {
+    let mut stalled = reference("generated-15x15-seed10");
+    assert!(!solve(&mut stalled, &mut Quiet).unwrap(), "The rules should get stuck on generated-15x15-seed10");
+
+    let mut group = c.benchmark_group("rule");
+    for name in rule_names() {
+        group.bench_function(name, |b| b.iter_batched(
+            || stalled.clone(),
+            |mut board| apply_rule(&mut board, name).unwrap(),
+            BatchSize::SmallInput));
+    }
+    group.finish();
+}
+
+fn bench_parsing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for &(size, name) in RULES_SOLVE.iter() {
+        let text = reference_text(name);
+        group.bench_function(size, |b| b.iter(|| parse_board(black_box(&text)).unwrap()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_solves, bench_rules, bench_parsing);
+criterion_main!(benches);
@@ -0,0 +1,100 @@
+/*
+ * Solve Battleship solitaire puzzles from C.
+ *
+ * - Boards are opaque, created by communicator_board_new and released by
+ *   communicator_board_free.
+ * - Strings returned by the library are released by communicator_string_free.
+ * - When a call fails, it returns NULL, -1, or an ERROR/INVALID value, and
+ *   communicator_last_error describes what went wrong. That message belongs
+ *   to the library, and lasts until the next call on the thread that fails.
+ * - Pass NULL options to communicator_solve for the defaults. A limit of 0
+ *   means no limit.
+ */
+
+#ifndef COMMUNICATOR_H
+#define COMMUNICATOR_H
+
+/* Generated by cbindgen from src/ffi.rs. Don't edit by hand. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+typedef enum CommunicatorSquare {
+  COMMUNICATOR_SQUARE_INVALID = -1,
+  COMMUNICATOR_SQUARE_UNKNOWN,
+  COMMUNICATOR_SQUARE_WATER,
+  COMMUNICATOR_SQUARE_ANY,
+  COMMUNICATOR_SQUARE_DOT,
+  COMMUNICATOR_SQUARE_LEFT_END,
+  COMMUNICATOR_SQUARE_RIGHT_END,
+  COMMUNICATOR_SQUARE_TOP_END,
+  COMMUNICATOR_SQUARE_BOTTOM_END,
+  COMMUNICATOR_SQUARE_VERTICAL_MIDDLE,
+  COMMUNICATOR_SQUARE_HORIZONTAL_MIDDLE,
+  COMMUNICATOR_SQUARE_ANY_MIDDLE,
+} CommunicatorSquare;
+
+typedef enum CommunicatorFleetSource {
+  COMMUNICATOR_FLEET_SOURCE_ERROR = -1,
+  COMMUNICATOR_FLEET_SOURCE_GIVEN,
+  COMMUNICATOR_FLEET_SOURCE_STANDARD,
+  COMMUNICATOR_FLEET_SOURCE_UNKNOWN,
+} CommunicatorFleetSource;
+
+typedef enum CommunicatorOutcome {
+  COMMUNICATOR_OUTCOME_ERROR = -1,
+  COMMUNICATOR_OUTCOME_SOLVED,
+  COMMUNICATOR_OUTCOME_STALLED,
+  COMMUNICATOR_OUTCOME_TIMED_OUT,
+  COMMUNICATOR_OUTCOME_PASS_LIMIT_REACHED,
+} CommunicatorOutcome;
+
+typedef struct CommunicatorBoard CommunicatorBoard;
+
+typedef struct CommunicatorSolveOptions {
+  uint32_t max_passes;
+  uint32_t timeout_ms;
+  bool search;
+} CommunicatorSolveOptions;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+const char *communicator_last_error(void);
+
+void communicator_string_free(char *string);
+
+struct CommunicatorBoard *communicator_board_new(const char *text);
+
+void communicator_board_free(struct CommunicatorBoard *board);
+
+ptrdiff_t communicator_board_num_rows(const struct CommunicatorBoard *board);
+
+ptrdiff_t communicator_board_num_cols(const struct CommunicatorBoard *board);
+
+enum CommunicatorSquare communicator_board_square(const struct CommunicatorBoard *board,
+                                                  size_t row_num,
+                                                  size_t col_num);
+
+ptrdiff_t communicator_board_row_remaining(const struct CommunicatorBoard *board, size_t row_num);
+
+ptrdiff_t communicator_board_col_remaining(const struct CommunicatorBoard *board, size_t col_num);
+
+ptrdiff_t communicator_board_ships_remaining(const struct CommunicatorBoard *board, size_t size);
+
+enum CommunicatorFleetSource communicator_board_fleet_source(const struct CommunicatorBoard *board);
+
+char *communicator_board_to_string(const struct CommunicatorBoard *board);
+
+struct CommunicatorSolveOptions communicator_solve_options_default(void);
+
+enum CommunicatorOutcome communicator_solve(struct CommunicatorBoard *board,
+                                            const struct CommunicatorSolveOptions *options);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* COMMUNICATOR_H */
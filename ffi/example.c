@@ -0,0 +1,78 @@
+/*
+ * Solve a puzzle read from stdin, using the C ABI.
+ *
+ * Build, after `cargo build`:
+ *   cc ffi/example.c -I ffi target/debug/libcommunicator.a -lpthread -ldl -lm -o example
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "communicator.h"
+
+static char *read_all(FILE *file) {
+    size_t size = 0, capacity = 256;
+    char *text = malloc(capacity);
+    int ch;
+
+    while (text && (ch = fgetc(file)) != EOF) {
+        if (size + 1 == capacity) {
+            capacity *= 2;
+            text = realloc(text, capacity);
+        }
+        if (text) {
+            text[size++] = (char) ch;
+        }
+    }
+    if (text) {
+        text[size] = '\0';
+    }
+
+    return text;
+}
+
+int main(void) {
+    char *text = read_all(stdin);
+    if (!text) {
+        fprintf(stderr, "Out of memory\n");
+        return 1;
+    }
+
+    CommunicatorBoard *board = communicator_board_new(text);
+    free(text);
+    if (!board) {
+        fprintf(stderr, "error: %s\n", communicator_last_error());
+        return 1;
+    }
+
+    printf("The board is %ld x %ld, with %ld ship squares left to find in row 0.\n",
+        (long) communicator_board_num_rows(board),
+        (long) communicator_board_num_cols(board),
+        (long) communicator_board_row_remaining(board, 0));
+
+    CommunicatorSolveOptions options = communicator_solve_options_default();
+    options.timeout_ms = 10000;
+    options.search = true;
+
+    CommunicatorOutcome outcome = communicator_solve(board, &options);
+    if (outcome == COMMUNICATOR_OUTCOME_ERROR) {
+        fprintf(stderr, "error: %s\n", communicator_last_error());
+        communicator_board_free(board);
+        return 1;
+    }
+
+    char *grid = communicator_board_to_string(board);
+    printf("%s:\n%s\n", outcome == COMMUNICATOR_OUTCOME_SOLVED ? "Solved" : "Not solved", grid);
+    communicator_string_free(grid);
+
+    printf("Square at row 0, col 1 is a top end: %s\n",
+        communicator_board_square(board, 0, 1) == COMMUNICATOR_SQUARE_TOP_END ? "yes" : "no");
+
+    communicator_board_free(board);
+
+    if (!communicator_board_new("not a board")) {
+        printf("Bad boards are reported: %s\n", communicator_last_error());
+    }
+
+    return 0;
+}
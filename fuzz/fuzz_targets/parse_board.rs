@@ -0,0 +1,21 @@
+// Any text should either parse, or be reported as a parse error. A board that
+// parses should also parse again once it's written out.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use communicator::parse_board;
+
+fuzz_target!(|data: &[u8]| {
+    let text = match std::str::from_utf8(data) {
+        Ok(text) => text,
+        Err(_)   => return,
+    };
+
+    if let Ok(board) = parse_board(text) {
+        let written = board.to_strings().join("\n");
+        if let Err(e) = parse_board(&written) {
+            panic!("Couldn't parse the written board:\n{}\nerror: {}", written, e);
+        }
+    }
+});
@@ -0,0 +1,27 @@
+// Whatever the board says, solving it should finish with an answer or an
+// error, never a panic
+#![no_main]
+
+use std::time::{Duration, Instant};
+
+use libfuzzer_sys::fuzz_target;
+
+use communicator::{parse_board, solve_with_config, Quiet, SolveConfig};
+
+fuzz_target!(|data: &[u8]| {
+    let text = match std::str::from_utf8(data) {
+        Ok(text) => text,
+        Err(_)   => return,
+    };
+
+    if let Ok(board) = parse_board(text) {
+        // Big boards with few clues can take the rules a while
+        let config = SolveConfig {
+            max_passes: Some(50),
+            deadline: Some(Instant::now() + Duration::from_secs(1)),
+            ..Default::default()
+        };
+
+        let _ = solve_with_config(board, config, &mut Quiet);
+    }
+});
@@ -1,5 +1,6 @@
+use std::fmt;
 use std::ops::Index;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::error::*;
 use crate::layout::*;
@@ -7,13 +8,62 @@ use crate::parse::*;
 use crate::ship::*;
 use crate::square::*;
 
+// A change to one square
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SquareChange {
+    pub row_num: usize,
+    pub col_num: usize,
+    pub before: Square,
+    pub after: Square,
+}
+
+// Where the board's fleet came from
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FleetSource {
+    // The ships: line
+    Given,
+
+    // There wasn't a ships: line, so the standard fleet with this name is
+    // assumed. It fits the size of the board, and the counts.
+    Standard(String),
+
+    // There wasn't a ships: line, and no standard fleet fits. The solver only
+    // knows the total size of the fleet, from the counts.
+    Unknown,
+}
+
+impl fmt::Display for FleetSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FleetSource::Given          => write!(f, "The fleet is from the ships: line"),
+            FleetSource::Standard(name) => write!(f, "There's no ships: line, so assuming the {} fleet", name),
+            FleetSource::Unknown        => write!(f,
+                "There's no ships: line, and no standard fleet fits, so the fleet is unknown. \
+                 Only the row and col counts constrain the ships."),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Board {
-    ships_to_find: HashMap<ExpectedShip, usize>, // ExpectedShip => count of ships remaining
+    // Ordered, so that the rules run the same way every time
+    ships_to_find: BTreeMap<ExpectedShip, usize>, // ExpectedShip => count of ships remaining
+    fleet_source: FleetSource,
     squares: Vec<Vec<Square>>,
     ship_squares_remaining_for_col: Vec<usize>,
     ship_squares_remaining_for_row: Vec<usize>,
     dirty: bool,
-    pub layout: Layout,
+
+    // Every change ever made, so the solver can look at just what changed
+    // since a rule last ran. The changes since clear_dirty start at clean_at.
+    history: Vec<SquareChange>,
+    clean_at: usize,
+
+    // The stamp of the last change to each row and col
+    row_stamps: Vec<usize>,
+    col_stamps: Vec<usize>,
+
+    pub(crate) layout: Layout,
 }
 
 impl Board {
@@ -22,29 +72,49 @@ impl Board {
     // Board creation
 
     pub fn new(text_lines : &[&str]) -> Result<Self> {        
-        let mut text = text_lines.join("\n");
-        text.push_str("\n.");
-
-        parse_board(&text)
+        parse_board(&text_lines.join("\n"))
     }
 
     pub fn new_from_data(squares: Vec<Vec<Square>>, 
         ship_squares_remaining_for_row: Vec<usize>,
         ship_squares_remaining_for_col: Vec<usize>,
-        ships_to_find: HashMap<usize, usize>) -> Self {
+        ships_to_find: HashMap<usize, usize>) -> Result<Self> {
+
+        ensure!(!squares.is_empty(), "A board needs at least one row");
+        let num_cols = squares[0].len();
+        ensure!(num_cols > 0, "A board needs at least one col");
+        for (row_num, row) in squares.iter().enumerate() {
+            ensure!(row.len() == num_cols,
+                "Row {} has {} squares, but row 0 has {}", row_num, row.len(), num_cols);
+        }
+        ensure!(ship_squares_remaining_for_row.len() == squares.len(),
+            "There are {} row counts for {} rows", ship_squares_remaining_for_row.len(), squares.len());
+        ensure!(ship_squares_remaining_for_col.len() == num_cols,
+            "There are {} col counts for {} cols", ship_squares_remaining_for_col.len(), num_cols);
+
+        // The text format has room for one digit per row/col
+        let counts = ship_squares_remaining_for_row.iter().chain(ship_squares_remaining_for_col.iter());
+        if let Some(count) = counts.cloned().find(|&count| count > 9) {
+            bail!("A count of {} is too many to write down", count);
+        }
 
         let layout = Layout {
             num_rows: squares.len(),
-            num_cols: squares[0].len(),            
+            num_cols,
         };
 
         let mut board = Board {
+            history: Vec::new(),
+            clean_at: 0,
+            row_stamps: vec![0; squares.len()],
+            col_stamps: vec![0; num_cols],
             squares,
             ship_squares_remaining_for_col,
             ship_squares_remaining_for_row,
             layout,
             dirty: false,
-            ships_to_find: Default::default()
+            ships_to_find: Default::default(),
+            fleet_source: if ships_to_find.is_empty() { FleetSource::Unknown } else { FleetSource::Given },
         };
 
         // Convert ships_to_find 
@@ -59,13 +129,58 @@ impl Board {
             });
         board.ships_to_find.extend(to_find_iter);
 
-        board
+        Ok(board)
+    }
+
+    // For a board without a fleet, assume the standard fleet for its size, if
+    // there is one and it covers as many squares as the counts say are ships
+    pub fn infer_fleet(&mut self) -> &FleetSource {
+        if self.fleet_source != FleetSource::Unknown {
+            return &self.fleet_source;
+        }
+
+        let name = format!("{}x{}", self.layout.num_rows, self.layout.num_cols);
+        if let Some(fleet) = standard_puzzle_fleet(&name) {
+            let fleet_squares = fleet.iter()
+                .map(|&(size, count)| size * count)
+                .sum::<usize>();
+            let clue_squares = (0 .. self.layout.num_rows)
+                .map(|row_num| self.ship_squares_total(self.layout.row(row_num)))
+                .sum::<usize>();
+
+            if fleet_squares == clue_squares {
+                self.ships_to_find = fleet.iter()
+                    .map(|&(size, count)| (ExpectedShip { size }, count))
+                    .collect();
+                self.fleet_source = FleetSource::Standard(format!("standard-{}", name));
+            }
+        }
+
+        &self.fleet_source
+    }
+
+    pub fn fleet_source(&self) -> &FleetSource {
+        &self.fleet_source
+    }
+
+    /////////////////////////////////////////////////////////////////////
+    //
+    // Reading squares
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    // The square at the given col and row. Panics if it's off the board.
+    pub fn square(&self, col_num: usize, row_num: usize) -> Square {
+        self.squares[row_num][col_num]
     }
 
     /////////////////////////////////////////////////////////////////////
     //
     // Printing / converting to string
 
+    // One digit per col. Boards never have counts of more than 9.
     fn format_col_headers(&self) -> String {
         let prefix = "  ".to_string(); // start the line with two blanks
         self.ship_squares_remaining_for_col.iter()
@@ -76,13 +191,13 @@ impl Board {
             })
     }
 
-    fn format_rows(&self) -> Vec<String> {
+    fn format_rows(&self, style: TextStyle) -> Vec<String> {
         self.squares.iter()
             .enumerate()
             .map(|(row_num, row)| {
                 let row_count = self.ship_squares_remaining_for_row[row_num];
                 let mut row_text = format!("{}|", row_count);
-                let squares = row.iter().map(Square::to_string);
+                let squares = row.iter().map(|square| square.to_char(style));
                 row_text.extend(squares);
 
                 row_text
@@ -91,7 +206,8 @@ impl Board {
     }
 
     fn format_ships_to_find(&self) -> Option<String> {
-        if self.ships_to_find.is_empty() {
+        // An assumed fleet wasn't in the text, so it isn't written back out
+        if self.ships_to_find.is_empty() || self.fleet_source != FleetSource::Given {
             return None;
         }
 
@@ -121,6 +237,10 @@ impl Board {
     }
 
     pub fn to_strings(&self) -> Vec<String> {
+        self.to_strings_with_style(TextStyle::Unicode)
+    }
+
+    pub fn to_strings_with_style(&self, style: TextStyle) -> Vec<String> {
         let mut out = Vec::new();
 
         if let Some(ships_row) = self.format_ships_to_find() {
@@ -130,14 +250,14 @@ impl Board {
         let header_row = self.format_col_headers();
         out.push(header_row);
 
-        let mut other_rows = self.format_rows();
+        let mut other_rows = self.format_rows(style);
         out.append(&mut other_rows);
 
         out
     }
 
-    pub fn print(&self) {
-        for str in self.to_strings() {
+    pub fn print(&self, style: TextStyle) {
+        for str in self.to_strings_with_style(style) {
             println!("{}", str);
         }        
     }
@@ -146,12 +266,51 @@ impl Board {
     //
     // Change tracking
 
-    pub fn dirty(&self) -> bool {
+    pub(crate) fn dirty(&self) -> bool {
         self.dirty
     }
 
-    pub fn clear_dirty(&mut self) {
-        self.dirty = false
+    pub(crate) fn clear_dirty(&mut self) {
+        self.dirty = false;
+        self.clean_at = self.history.len();
+    }
+
+    // The squares that changed since clear_dirty was called, in the order they changed
+    pub(crate) fn changes(&self) -> &[SquareChange] {
+        &self.history[self.clean_at ..]
+    }
+
+    // Every change gets a larger stamp than the ones before it. Unlike dirty,
+    // stamps are never cleared.
+    pub(crate) fn stamp(&self) -> usize {
+        self.history.len()
+    }
+
+    // The squares that changed since the given stamp, in the order they changed
+    pub(crate) fn changes_since(&self, since: usize) -> &[SquareChange] {
+        &self.history[since ..]
+    }
+
+    // Has any square in the row/col changed since the given stamp? Its count of
+    // ship squares remaining can only change along with one of its squares.
+    pub(crate) fn changed_in(&self, row_or_col: RowOrCol, since: usize) -> bool {
+        let stamp = match row_or_col.axis {
+            Axis::Row => self.row_stamps[row_or_col.index],
+            Axis::Col => self.col_stamps[row_or_col.index],
+        };
+
+        stamp > since
+    }
+
+    // Has the row/col, or a row/col on either side of it, changed since the given stamp?
+    pub(crate) fn changed_near_line(&self, row_or_col: RowOrCol, since: usize) -> bool {
+        let num_lines = match row_or_col.axis {
+            Axis::Row => self.layout.num_rows,
+            Axis::Col => self.layout.num_cols,
+        };
+
+        (row_or_col.index.saturating_sub(1) ..= (row_or_col.index + 1).min(num_lines - 1))
+            .any(|index| self.changed_in(self.layout.row_or_col(row_or_col.axis, index), since))
     }
 
     /////////////////////////////////////////////////////////////////////
@@ -160,7 +319,7 @@ impl Board {
 
 
     // changed: set to true if board[index] != value, othewise do not set
-    pub fn set(&mut self, index: Coord, new_value: Square) -> Result<()> {
+    pub(crate) fn set(&mut self, index: Coord, new_value: Square) -> Result<()> {
         let curr_value = self[index];
 
         if curr_value == new_value {
@@ -187,20 +346,37 @@ impl Board {
                 new_value, index);
         }
 
-        self.squares[index.row_num][index.col_num] = new_value;
-
         // Update ships remaining
         if new_value.is_ship() && !curr_value.is_ship() {
+            ensure!(self.ship_squares_remaining_for_row[index.row_num] > 0,
+                "Attempting to add a ship to row {}, which has no ship squares remaining. index: {:?}",
+                index.row_num, index);
+            ensure!(self.ship_squares_remaining_for_col[index.col_num] > 0,
+                "Attempting to add a ship to col {}, which has no ship squares remaining. index: {:?}",
+                index.col_num, index);
+
             self.ship_squares_remaining_for_row[index.row_num] -= 1;
             self.ship_squares_remaining_for_col[index.col_num] -= 1;
         }
-        
+
+        self.squares[index.row_num][index.col_num] = new_value;
+
+        self.history.push(SquareChange {
+            row_num: index.row_num,
+            col_num: index.col_num,
+            before: curr_value,
+            after: new_value,
+        });
         self.dirty = true;
+
+        self.row_stamps[index.row_num] = self.history.len();
+        self.col_stamps[index.col_num] = self.history.len();
+
         Ok(())
     }
 
     // In the given row/col, replace all Unknown squares with the specified value
-    pub fn replace_unknown(&mut self, row_or_col: RowOrCol, new_value: Square) -> Result<()> {
+    pub(crate) fn replace_unknown(&mut self, row_or_col: RowOrCol, new_value: Square) -> Result<()> {
         for coord in row_or_col.coords() {
             if self[coord] == Square::Unknown { 
                 self.set(coord, new_value)?
@@ -221,15 +397,32 @@ impl Board {
     }
 
     // Count number of ships remaining in the given row/col
-    pub fn ship_squares_remaining(&self, row_or_col: RowOrCol) -> usize {
+    pub(crate) fn ship_squares_remaining(&self, row_or_col: RowOrCol) -> usize {
         match row_or_col.axis {
             Axis::Row => self.ship_squares_remaining_for_row[row_or_col.index],
             Axis::Col => self.ship_squares_remaining_for_col[row_or_col.index],
         }
     }
 
+    // Count all the ship squares in the given row/col: the ones still to be found,
+    // plus the ones already on the board
+    pub(crate) fn ship_squares_total(&self, row_or_col: RowOrCol) -> usize {
+        let num_placed = row_or_col.coords()
+            .filter(|&coord| self[coord].is_ship())
+            .count();
+
+        self.ship_squares_remaining(row_or_col) + num_placed
+    }
+
+    // Enumerate every ship in the puzzle, found or not, along with how many of
+    // that size the puzzle contains in total
+    pub fn fleet<'a>(&'a self) -> impl Iterator<Item = (ExpectedShip, usize)> + 'a {
+        self.ships_to_find.iter()
+            .map(|(&expected_ship, &count)| (expected_ship, count))
+    }
+
     // Enumerate all the sizes of ships that remain to be found
-    pub fn remaining_expected_ships<'a>(&'a self) -> impl Iterator<Item = ExpectedShip> + 'a {
+    pub(crate) fn remaining_expected_ships<'a>(&'a self) -> impl Iterator<Item = ExpectedShip> + 'a {
         self.ships_to_find.iter()
             .filter_map(|(&expected_ship, &count)|
                 if count > 0 {
@@ -242,17 +435,29 @@ impl Board {
     }
 
     // How many ships of a given size remain to be found
-    pub fn num_remaining_ships_to_find(&self, expected_ship: ExpectedShip) -> usize {
+    pub(crate) fn num_remaining_ships_to_find(&self, expected_ship: ExpectedShip) -> usize {
         if let Some(&total) = self.ships_to_find.get(&expected_ship) {
             let found = self.count_found_ships(expected_ship);
 
-            total - found
+            // Too many found is an error that check_fleet reports
+            total.saturating_sub(found)
         }
         else {
             0
         }
     }
 
+    // Error if the board holds more complete ships of some size than the puzzle contains
+    pub(crate) fn check_fleet(&self) -> Result<()> {
+        for (&expected_ship, &total) in self.ships_to_find.iter() {
+            let found = self.count_found_ships(expected_ship);
+            ensure!(found <= total,
+                "Found {} ships of size {}, but the puzzle only has {}", found, expected_ship.size, total);
+        }
+
+        Ok(())
+    }
+
     // Count how many ships of a given size are found
     fn count_found_ships(&self, expected_ship: ExpectedShip) -> usize {
         self.layout.possible_heads_for_ship(expected_ship)
@@ -265,7 +470,7 @@ impl Board {
     }
 
     // Does a specific ship (size + axis) exist at these coords?
-    fn ship_is_found(&self, ship: Ship) -> bool {
+    pub(crate) fn ship_is_found(&self, ship: Ship) -> bool {
         match ship.coords() {
             None         => false, // ship would be out of bounds
             Some(coords) => coords
@@ -294,7 +499,7 @@ mod test {
 
 	#[test]
 	fn it_sets_changed() -> Result<()> {
-	    let mut board = Board::new(&vec![
+	    let mut board = Board::new(&[
 	        "  001",
 	        "0|   ",
 	        "1|~  ",
@@ -304,16 +509,203 @@ mod test {
 	    // Set a squre to its current value => dirty is false
 	    let coord = layout.coord(0, 1);
 	    board.set(coord, Square::Water)?;
-	    assert_eq!(board.dirty, false);
+	    assert!(!board.dirty);
 
 	    // Set a square to a new value => dirty is true
 	    let coord = layout.coord(0, 0);
 	    board.set(coord, Square::Water)?;
-	    assert_eq!(board.dirty, true);
+	    assert!(board.dirty);
 
         Ok(())
 	}
 
+    #[test]
+    fn it_sets_squares() -> Result<()> {
+        let mut board = Board::new(&[
+            "  10",
+            "1|  ",
+        ])?;
+        let layout = board.layout;
+
+        board.set(layout.coord(0, 0), Square::ShipSquare(ShipSquare::Dot))?;
+        assert_eq!(board.to_strings(), ["  00", "0|• "]);
+        assert_eq!(board.changes().len(), 1);
+
+        // Water can't become a ship
+        board.set(layout.coord(1, 0), Square::Water)?;
+        assert!(board.set(layout.coord(1, 0), Square::ShipSquare(ShipSquare::Any)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_records_changes() -> Result<()> {
+        let mut board = Board::new(&[
+            "  001",
+            "0|   ",
+            "1|~  ",
+        ])?;
+        let layout = board.layout;
+
+        board.set(layout.coord(0, 1), Square::Water)?;
+        board.set(layout.coord(2, 1), Square::ShipSquare(ShipSquare::Any))?;
+        board.set(layout.coord(2, 1), Square::ShipSquare(ShipSquare::Dot))?;
+        assert_eq!(board.changes(), &[
+            SquareChange { row_num: 1, col_num: 2, before: Square::Unknown, after: Square::ShipSquare(ShipSquare::Any) },
+            SquareChange { row_num: 1, col_num: 2, before: Square::ShipSquare(ShipSquare::Any), after: Square::ShipSquare(ShipSquare::Dot) },
+        ]);
+
+        board.clear_dirty();
+        assert!(board.changes().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_stamps_changes() -> Result<()> {
+        let mut board = Board::new(&[
+            "  0001",
+            "0|    ",
+            "0|    ",
+            "1|    ",
+        ])?;
+        let layout = board.layout;
+
+        board.set(layout.coord(0, 0), Square::Water)?;
+        board.clear_dirty();
+
+        let before = board.stamp();
+        board.set(layout.coord(3, 2), Square::ShipSquare(ShipSquare::Any))?;
+        board.set(layout.coord(3, 2), Square::ShipSquare(ShipSquare::Any))?; // Not a change
+        assert_eq!(board.stamp(), before + 1);
+        assert_eq!(board.changes_since(before), board.changes());
+        assert_eq!(board.changes_since(0).len(), 2);
+
+        // The row and col of the change, and the ones either side of them
+        assert!(board.changed_in(layout.row(2), before));
+        assert!(!board.changed_in(layout.row(1), before));
+        assert!(!board.changed_in(layout.row(2), board.stamp()));
+        assert!(board.changed_near_line(layout.row(1), before));
+        assert!(!board.changed_near_line(layout.row(0), before));
+        assert!(board.changed_near_line(layout.col(2), before));
+        assert!(!board.changed_near_line(layout.col(1), before));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_writes_either_style() -> Result<()> {
+        let unicode = [
+            "  1103",
+            "2|•~ ^",
+            "1|~~~☐",
+            "0|~~~v",
+        ];
+        let ascii = [
+            "  1103",
+            "2|o~ ^",
+            "1|~~~+",
+            "0|~~~v",
+        ];
+
+        // Either style reads in, and either can be written out
+        for text in [unicode, ascii].iter() {
+            let board = Board::new(text)?;
+            assert_eq!(board.to_strings(), unicode);
+            assert_eq!(board.to_strings_with_style(TextStyle::Unicode), unicode);
+            assert_eq!(board.to_strings_with_style(TextStyle::Ascii), ascii);
+        }
+
+        let dot = Square::ShipSquare(ShipSquare::Dot);
+        assert_eq!(format!("{}{:#}", dot, dot), "•o");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_infers_standard_fleets() -> Result<()> {
+        let text = [
+            "  302113",
+            "2|      ",
+            "1|      ",
+            "3|      ",
+            "0|      ",
+            "3|      ",
+            "1|      ",
+        ];
+        let board = Board::new(&text)?;
+
+        assert_eq!(*board.fleet_source(), FleetSource::Standard("standard-6x6".to_string()));
+        assert_eq!(board.fleet().collect::<Vec<_>>(), [
+            (ExpectedShip { size: 1 }, 3),
+            (ExpectedShip { size: 2 }, 2),
+            (ExpectedShip { size: 3 }, 1),
+        ]);
+
+        // The assumed fleet isn't written out
+        assert_eq!(board.to_strings(), text);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_leaves_fleets_unknown_when_nothing_fits() -> Result<()> {
+        // 6x6, but the counts only add up to 9
+        let board = Board::new(&[
+            "  302112",
+            "2|      ",
+            "1|      ",
+            "3|      ",
+            "0|      ",
+            "2|      ",
+            "1|      ",
+        ])?;
+        assert_eq!(*board.fleet_source(), FleetSource::Unknown);
+        assert_eq!(board.fleet().count(), 0);
+
+        let board = Board::new(&[
+            "ships: 1sq x 1.",
+            "  1",
+            "1| ",
+        ])?;
+        assert_eq!(*board.fleet_source(), FleetSource::Given);
+
+        Ok(())
+    }
+
+    fn data_error(squares: Vec<Vec<Square>>, for_row: Vec<usize>, for_col: Vec<usize>) -> String {
+        match Board::new_from_data(squares, for_row, for_col, HashMap::new()) {
+            Err(error) => error.to_string(),
+            Ok(_)      => panic!("Expected an error"),
+        }
+    }
+
+    #[test]
+    fn it_rejects_data_that_is_not_a_board() {
+        assert_eq!(data_error(vec![], vec![], vec![1]),
+            "A board needs at least one row");
+        assert_eq!(data_error(vec![vec![Square::Unknown; 2], vec![Square::Unknown]], vec![0, 0], vec![0, 0]),
+            "Row 1 has 1 squares, but row 0 has 2");
+        assert_eq!(data_error(vec![vec![Square::Unknown; 2]], vec![0], vec![0]),
+            "There are 1 col counts for 2 cols");
+        assert_eq!(data_error(vec![vec![Square::Unknown; 10]], vec![10], vec![1; 10]),
+            "A count of 10 is too many to write down");
+    }
+
+    #[test]
+    fn it_writes_too_many_found_ships_as_none_remaining() -> Result<()> {
+        let board = Board::new(&[
+            "ships: 1sq x 0.",
+            "  1",
+            "0|•",
+        ])?;
+
+        assert_eq!(board.to_strings()[0], "ships: 1sq x 0.");
+        assert!(board.check_fleet().is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn it_returns_num_rows() -> Result<()> {
         let board = make_test_board()?;
@@ -342,7 +734,7 @@ mod test {
         board.set(coord1, Square::Water)?;
         
         assert_eq!(board[coord1], Square::Water);
-        assert_eq!(board.dirty, true);
+        assert!(board.dirty);
 
         let coord2 = layout.coord(3, 1);
         assert_eq!(board[coord2], Square::Water);
@@ -371,7 +763,7 @@ mod test {
 
     #[test]
     fn it_counts_ship_squares_remaining() -> Result<()> {
-        let board = Board::new(&vec![
+        let board = Board::new(&[
             "  0123", 
             "9|    ", 
             "8|    ",
@@ -415,7 +807,7 @@ mod test {
             board.ship_squares_remaining(layout.col(coord.col_num)),
             1 - 1);
 
-        assert_eq!(board.dirty, true);
+        assert!(board.dirty);
 
         Ok(())
     }
@@ -423,7 +815,7 @@ mod test {
     // TODO: This test should move to layout
     #[test]
     fn it_accesses_col_contents() -> Result<()> {
-        let board = Board::new(&vec![
+        let board = Board::new(&[
             "  000",
             "0| ^ ",
             "0| | ",
@@ -465,7 +857,7 @@ mod test_find_ships {
 
     #[test]
     fn it_finds_ship() -> Result<()> {
-        let board = Board::new(&vec![
+        let board = Board::new(&[
             "  000",
             "0|  ^",
             "0| ~|",
@@ -480,28 +872,28 @@ mod test_find_ships {
         let found_ship = board.ship_is_found(
             Ship { size: 4, head: ShipHead {origin, incrementing_axis: Axis::Row }}
             );
-        assert_eq!(found_ship, true);
+        assert!(found_ship);
 
         // Find horizontal ship
         let origin = board.layout.coord(0, 5);
         let found_ship = board.ship_is_found(
             Ship { size: 2, head: ShipHead {origin, incrementing_axis: Axis::Col }}
             );
-        assert_eq!(found_ship, true);        
+        assert!(found_ship);        
 
         // Finds dot
         let origin = board.layout.coord(0, 2);
         let found_ship = board.ship_is_found(
             Ship { size: 1, head: ShipHead {origin, incrementing_axis: Axis::Row }}
             );
-        assert_eq!(found_ship, true);        
+        assert!(found_ship);        
 
         // Does not match when size < ship size
         let origin = board.layout.coord(2, 0);
         let found_ship = board.ship_is_found(
             Ship { size: 3, head: ShipHead {origin, incrementing_axis: Axis::Row }}
             );
-        assert_eq!(found_ship, false);
+        assert!(!found_ship);
 
         // Does not match when size > ship size.
         // We ask for a ship of size 3, but the ship 
@@ -510,14 +902,14 @@ mod test_find_ships {
         let found_ship = board.ship_is_found(
             Ship { size: 5, head: ShipHead {origin, incrementing_axis: Axis::Row }}
             );
-        assert_eq!(found_ship, false);     
+        assert!(!found_ship);     
 
         Ok(())           
     }
 
     #[test]
     fn it_counts_ships() -> Result<()> {
-        let board = Board::new(&vec![
+        let board = Board::new(&[
             "  00000",
             "0|  ^ ^",
             "0| ~| |",
@@ -0,0 +1,619 @@
+/////////////////////////////////////////////////////////////////////
+//
+// Encode a board as a CNF formula, so it can be handed to an external
+// SAT solver in DIMACS format. Then decode the solver's model back
+// into a solved board.
+//
+// Variables:
+// - One per square: "this square is a ship". Numbered 1 ..= rows * cols,
+//   in row-major order, so they're easy to find in a model.
+// - One per possible placement: "a ship of size k has its head here"
+// - Auxiliary variables for the cardinality constraints
+
+use std::collections::HashSet;
+use std::fmt::Write as FmtWrite;
+use std::io::Write;
+
+use crate::board::*;
+use crate::error::*;
+use crate::layout::*;
+use crate::ship::*;
+use crate::square::*;
+
+// Write the board as a DIMACS CNF file, for an external SAT solver
+pub fn write_dimacs(board: &Board, writer: &mut impl Write) -> Result<()> {
+    Cnf::from_board(board).write_dimacs(writer)
+}
+
+// Read a SAT solver's model of the file that write_dimacs wrote, and return the solved board
+pub fn decode_sat_model(board: &Board, model_text: &str) -> Result<Board> {
+    Cnf::from_board(board).decode(model_text)
+}
+
+// A DIMACS literal: a positive variable number, or a negated one.
+pub(crate) type Literal = i32;
+
+pub(crate) struct Cnf<'a> {
+    board: &'a Board,
+    num_vars: usize,
+    clauses: Vec<Vec<Literal>>,
+    placements: Vec<(Ship<'a>, Literal)>,
+
+    // A variable that's always true. Used as the constant in the cardinality constraints.
+    true_lit: Literal,
+}
+
+impl<'a> Cnf<'a> {
+    pub fn from_board(board: &'a Board) -> Self {
+        let layout = &board.layout;
+        let num_squares = layout.num_rows * layout.num_cols;
+
+        let mut cnf = Cnf {
+            board,
+            num_vars: num_squares,
+            clauses: Vec::new(),
+            placements: Vec::new(),
+            true_lit: 0,
+        };
+        cnf.true_lit = cnf.new_var();
+        cnf.clauses.push(vec![cnf.true_lit]);
+
+        cnf.add_placements();
+        cnf.add_givens();
+        cnf.add_line_counts();
+        cnf.add_fleet_counts();
+
+        cnf
+    }
+
+    /////////////////////////////////////////////////////////////////////
+    //
+    // Accessors, for the tests
+
+    #[cfg(test)]
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    #[cfg(test)]
+    pub fn clauses(&self) -> &[Vec<Literal>] {
+        &self.clauses
+    }
+
+    // The variable that's true when the square at coord is a ship
+    pub fn square_var(&self, coord: Coord) -> Literal {
+        (coord.row_num * self.board.layout.num_cols + coord.col_num + 1) as Literal
+    }
+
+    /////////////////////////////////////////////////////////////////////
+    //
+    // Building clauses
+
+    fn new_var(&mut self) -> Literal {
+        self.num_vars += 1;
+        self.num_vars as Literal
+    }
+
+    // Add a clause, simplifying away the constant true variable.
+    fn add_clause(&mut self, clause: &[Literal]) {
+        if clause.contains(&self.true_lit) {
+            return; // always satisfied
+        }
+
+        let false_lit = -self.true_lit;
+        let clause = clause.iter()
+            .cloned()
+            .filter(|&lit| lit != false_lit)
+            .collect();
+
+        // An empty clause can never be satisfied. We still emit it, so the
+        // solver reports the puzzle as unsatisfiable.
+        self.clauses.push(clause);
+    }
+
+    // Which sizes of ship might appear on the board. If the puzzle doesn't
+    // say, allow anything that fits.
+    fn ship_sizes(&self) -> Vec<usize> {
+        let mut sizes = self.board.fleet()
+            .map(|(expected_ship, _)| expected_ship.size)
+            .collect::<Vec<_>>();
+
+        if sizes.is_empty() {
+            let layout = self.board.layout;
+            sizes = (1 ..= layout.num_rows.max(layout.num_cols)).collect();
+        }
+
+        sizes.sort();
+        sizes
+    }
+
+    // Every placement implies that its squares are ships, and that the squares around
+    // it are water. Every ship square must be covered by some placement.
+    //
+    // The water around each placement also stops two placements from overlapping, so
+    // we don't need a separate "at most one placement per square" constraint.
+    fn add_placements(&mut self) {
+        let board = self.board;
+        let layout = &board.layout;
+
+        for size in self.ship_sizes() {
+            let expected_ship = ExpectedShip { size };
+
+            for ship_head in layout.possible_heads_for_ship(expected_ship) {
+                let ship = ship_head.to_ship(expected_ship);
+                let placement_var = self.new_var();
+                self.placements.push((ship, placement_var));
+            }
+        }
+
+        let mut covering: Vec<Vec<Literal>> = vec![Vec::new(); layout.num_rows * layout.num_cols];
+
+        for (ship, placement_var) in self.placements.clone() {
+            let ship_coords = ship.coords().unwrap().collect::<Vec<_>>();
+
+            let water_coords = ship_coords.iter()
+                .enumerate()
+                .flat_map(|(square_idx, coord)| {
+                    ship.expected_square_for_idx(square_idx)
+                        .water_neighbors()
+                        .into_iter()
+                        .filter_map(move |neighbor| coord.neighbor(neighbor))
+                })
+                .filter(|coord| !ship_coords.contains(coord))
+                .collect::<HashSet<_>>();
+
+            for &coord in ship_coords.iter() {
+                let square_var = self.square_var(coord);
+                self.add_clause(&[-placement_var, square_var]);
+                covering[square_var as usize - 1].push(placement_var);
+            }
+
+            for coord in water_coords {
+                let square_var = self.square_var(coord);
+                self.add_clause(&[-placement_var, -square_var]);
+            }
+        }
+
+        for coord in layout.all_coordinates() {
+            let square_var = self.square_var(coord);
+            let mut clause = covering[square_var as usize - 1].clone();
+            clause.push(-square_var);
+            self.add_clause(&clause);
+        }
+    }
+
+    // Squares that are already filled in. A specific kind of ship square (an end,
+    // a middle, a dot) must be covered by a placement that puts that kind of
+    // square there.
+    fn add_givens(&mut self) {
+        let board = self.board;
+
+        for coord in board.layout.all_coordinates() {
+            let square_var = self.square_var(coord);
+
+            let ship_square = match board[coord] {
+                Square::Unknown                  => continue,
+                Square::Water                    => {
+                    self.add_clause(&[-square_var]);
+                    continue;
+                },
+                Square::ShipSquare(ship_square)  => ship_square,
+            };
+
+            self.add_clause(&[square_var]);
+            if ship_square == ShipSquare::Any {
+                continue;
+            }
+
+            let clause = self.placements.iter()
+                .filter(|(ship, _)| {
+                    ship.coords().unwrap()
+                        .enumerate()
+                        .any(|(square_idx, ship_coord)| {
+                            let expected = ship.expected_square_for_idx(square_idx);
+                            ship_coord == coord && ship_square.can_refine_to(expected)
+                        })
+                })
+                .map(|&(_, placement_var)| placement_var)
+                .collect::<Vec<_>>();
+
+            self.add_clause(&clause);
+        }
+    }
+
+    // Each row/col contains exactly the number of ship squares given by its clue
+    fn add_line_counts(&mut self) {
+        let board = self.board;
+
+        for row_or_col in board.layout.rows_and_cols() {
+            let square_vars = row_or_col.coords()
+                .map(|coord| self.square_var(coord))
+                .collect::<Vec<_>>();
+            let total = board.ship_squares_total(row_or_col);
+
+            self.add_exactly(&square_vars, total);
+        }
+    }
+
+    // The puzzle contains exactly the expected number of ships of each size
+    fn add_fleet_counts(&mut self) {
+        let board = self.board;
+
+        for (expected_ship, count) in board.fleet() {
+            let placement_vars = self.placements.iter()
+                .filter(|(ship, _)| ship.size == expected_ship.size)
+                .map(|&(_, placement_var)| placement_var)
+                .collect::<Vec<_>>();
+
+            self.add_exactly(&placement_vars, count);
+        }
+    }
+
+    // Require exactly `k` of `lits` to be true, using a sequential counter.
+    //
+    // Register (i, j) is true iff at least j of the first i literals are true.
+    // We only need registers up to k + 1: "at least k" and "not at least k + 1".
+    fn add_exactly(&mut self, lits: &[Literal], k: usize) {
+        let false_lit = -self.true_lit;
+        let width = k + 1;
+
+        // registers for zero literals: "at least j of nothing" is false
+        let mut prev = vec![false_lit; width];
+
+        for (i, &lit) in lits.iter().enumerate() {
+            let mut curr = Vec::with_capacity(width);
+
+            for j in 1 ..= width {
+                if j > i + 1 {
+                    curr.push(false_lit); // can't have more true literals than we've seen
+                    continue;
+                }
+
+                let register = self.new_var();
+                let at_least_j     = prev[j - 1];
+                let at_least_j_sub = if j == 1 { self.true_lit } else { prev[j - 2] };
+
+                // register <-> at_least_j || (at_least_j_sub && lit)
+                self.add_clause(&[-at_least_j, register]);
+                self.add_clause(&[-at_least_j_sub, -lit, register]);
+                self.add_clause(&[-register, at_least_j, at_least_j_sub]);
+                self.add_clause(&[-register, at_least_j, lit]);
+
+                curr.push(register);
+            }
+
+            prev = curr;
+        }
+
+        if k > 0 {
+            self.add_clause(&[prev[k - 1]]);
+        }
+        self.add_clause(&[-prev[k]]);
+    }
+
+    /////////////////////////////////////////////////////////////////////
+    //
+    // DIMACS output
+
+    pub fn to_dimacs(&self) -> String {
+        let layout = self.board.layout;
+        let mut out = String::new();
+
+        // Writing to a String can't fail
+        writeln!(out, "c battleship puzzle, {} rows x {} cols", layout.num_rows, layout.num_cols).unwrap();
+        writeln!(out, "c variables 1 to {} are the squares, in row-major order",
+            layout.num_rows * layout.num_cols).unwrap();
+        writeln!(out, "p cnf {} {}", self.num_vars, self.clauses.len()).unwrap();
+
+        for clause in self.clauses.iter() {
+            for lit in clause {
+                write!(out, "{} ", lit).unwrap();
+            }
+            out.push_str("0\n");
+        }
+
+        out
+    }
+
+    pub fn write_dimacs(&self, writer: &mut impl Write) -> Result<()> {
+        writer.write_all(self.to_dimacs().as_bytes())
+            .chain_err(|| "Unable to write DIMACS output")
+    }
+
+    /////////////////////////////////////////////////////////////////////
+    //
+    // Decoding a model
+
+    // Read the output of a SAT solver, and fill in a copy of the board with the solution.
+    //
+    // Accepts the usual competition format ("s SATISFIABLE", then "v" lines of literals),
+    // or a bare list of literals.
+    pub fn decode(&self, model_text: &str) -> Result<Board> {
+        let mut true_vars = HashSet::new();
+
+        for line in model_text.lines() {
+            let line = line.trim();
+
+            if line.starts_with('c') || line.is_empty() {
+                continue;
+            }
+
+            if let Some(status) = line.strip_prefix('s') {
+                ensure!(status.trim() == "SATISFIABLE",
+                    "SAT solver did not find a solution: {}", status.trim());
+                continue;
+            }
+
+            let literals = line.strip_prefix('v').unwrap_or(line);
+            for token in literals.split_whitespace() {
+                let lit = token.parse::<Literal>()
+                    .chain_err(|| format!("Invalid literal in SAT model: {:?}", token))?;
+
+                if lit > 0 {
+                    true_vars.insert(lit);
+                }
+            }
+        }
+
+        let mut board = self.board.clone();
+
+        for &(ship, placement_var) in self.placements.iter() {
+            if !true_vars.contains(&placement_var) {
+                continue;
+            }
+
+            for (square_idx, coord) in ship.coords().unwrap().enumerate() {
+                let expected = ship.expected_square_for_idx(square_idx);
+                board.set(coord, Square::ShipSquare(expected))?;
+            }
+        }
+
+        let layout = board.layout;
+        for coord in layout.all_coordinates() {
+            let is_ship = true_vars.contains(&self.square_var(coord));
+            ensure!(is_ship == board[coord].is_ship(),
+                "SAT model is inconsistent at {:?}", coord);
+
+            if board[coord] == Square::Unknown {
+                board.set(coord, Square::Water)?;
+            }
+        }
+
+        Ok(board)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    // A small DPLL solver, so we can check the encoding without an external SAT binary.
+    // Returns the set of true variables.
+    fn dpll(clauses: &[Vec<Literal>], assignment: &mut HashMap<Literal, bool>) -> bool {
+        // Unit propagation
+        loop {
+            let mut changed = false;
+
+            for clause in clauses {
+                let mut unassigned = None;
+                let mut num_unassigned = 0;
+                let mut satisfied = false;
+
+                for &lit in clause {
+                    match assignment.get(&lit.abs()) {
+                        Some(&value) => if value == (lit > 0) { satisfied = true; break; },
+                        None         => { num_unassigned += 1; unassigned = Some(lit); },
+                    }
+                }
+
+                if satisfied {
+                    continue;
+                }
+                match num_unassigned {
+                    0 => return false,
+                    1 => {
+                        let lit = unassigned.unwrap();
+                        assignment.insert(lit.abs(), lit > 0);
+                        changed = true;
+                    },
+                    _ => {},
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        let branch_var = clauses.iter()
+            .flatten()
+            .map(|lit| lit.abs())
+            .find(|var| !assignment.contains_key(var));
+
+        match branch_var {
+            None => true,
+            Some(var) => [true, false].iter().any(|&value| {
+                let mut attempt = assignment.clone();
+                attempt.insert(var, value);
+
+                if dpll(clauses, &mut attempt) {
+                    *assignment = attempt;
+                    true
+                }
+                else {
+                    false
+                }
+            }),
+        }
+    }
+
+    fn solve_with_dpll(board: &Board) -> Result<Board> {
+        let cnf = Cnf::from_board(board);
+        let mut assignment = HashMap::new();
+
+        let model = if dpll(cnf.clauses(), &mut assignment) {
+            let literals = assignment.iter()
+                .map(|(&var, &value)| if value { var.to_string() } else { (-var).to_string() })
+                .collect::<Vec<_>>();
+            format!("s SATISFIABLE\nv {} 0\n", literals.join(" "))
+        }
+        else {
+            "s UNSATISFIABLE\n".to_string()
+        };
+
+        cnf.decode(&model)
+    }
+
+    #[test]
+    fn it_numbers_squares_in_row_major_order() -> Result<()> {
+        let board = Board::new(&[
+            "  000",
+            "0|   ",
+            "0|   ",
+        ])?;
+        let cnf = Cnf::from_board(&board);
+
+        assert_eq!(cnf.square_var(board.layout.coord(0, 0)), 1);
+        assert_eq!(cnf.square_var(board.layout.coord(2, 0)), 3);
+        assert_eq!(cnf.square_var(board.layout.coord(0, 1)), 4);
+        assert_eq!(cnf.square_var(board.layout.coord(2, 1)), 6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_writes_dimacs_header() -> Result<()> {
+        let board = Board::new(&[
+            "ships: 1sq x 1.",
+            "  010",
+            "1|   ",
+        ])?;
+        let cnf = Cnf::from_board(&board);
+        let dimacs = cnf.to_dimacs();
+
+        let header = format!("p cnf {} {}", cnf.num_vars(), cnf.clauses().len());
+        assert!(dimacs.lines().any(|line| line == header));
+
+        // Every clause line is terminated by 0
+        assert!(dimacs.lines()
+            .filter(|line| !line.starts_with('c') && !line.starts_with('p'))
+            .all(|line| line.ends_with('0')));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_solves_small_puzzle() -> Result<()> {
+        let board = Board::new(&[
+            "ships: 3sq x 1, 1sq x 2.",
+            "  2111",
+            "3|    ",
+            "0|    ",
+            "2|    ",
+        ])?;
+
+        let solved = solve_with_dpll(&board)?;
+
+        assert_eq!(solved.to_strings()[1..].to_vec(), vec![
+            "  0000",
+            "0|<->~",
+            "0|~~~~",
+            "0|•~~•",
+        ]);
+        assert!(solved.is_solved());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_respects_givens() -> Result<()> {
+        let board = Board::new(&[
+            "ships: 2sq x 1.",
+            "  01",
+            "1|  ",
+            "0| v",
+        ])?;
+        let solved = solve_with_dpll(&board)?;
+        assert_eq!(solved.to_strings()[1..].to_vec(), vec![
+            "  00",
+            "0|~^",
+            "0|~v",
+        ]);
+
+        // No room for the left end of the ship
+        let board = Board::new(&[
+            "ships: 2sq x 1.",
+            "  01",
+            "1|> ",
+        ])?;
+        assert!(solve_with_dpll(&board).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_detects_wrong_fleet() -> Result<()> {
+        // 4 ship squares, but only a 2-ship
+        let board = Board::new(&[
+            "ships: 2sq x 1.",
+            "  22",
+            "2|  ",
+            "0|  ",
+            "2|  ",
+        ])?;
+        let cnf = Cnf::from_board(&board);
+        let mut assignment = HashMap::new();
+        assert!(!dpll(cnf.clauses(), &mut assignment));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_matches_rule_based_solver() -> Result<()> {
+        let text = [
+            "ships: 4sq x 1, 3sq x 1, ",
+            "       2sq x 2, 1sq x 3.",
+            "  1420213",
+            "2|       ",
+            "2|   <   ",
+            "1|       ",
+            "1|       ",
+            "3|       ",
+            "1|       ",
+            "3|       ",
+        ];
+
+        let mut rule_board = Board::new(&text)?;
+        assert!(crate::solve::solve(&mut rule_board, &mut crate::solve::Quiet)?);
+
+        let sat_board = solve_with_dpll(&Board::new(&text)?)?;
+
+        // Compare the grids. The "ships:" line reports different remaining counts
+        // because of how the boards were filled in.
+        let grid = |board: &Board| board.to_strings().into_iter()
+            .filter(|line| !line.starts_with("ships:"))
+            .collect::<Vec<_>>();
+        assert_eq!(grid(&sat_board), grid(&rule_board));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_reports_unsatisfiable_model() -> Result<()> {
+        let board = Board::new(&[
+            "  0",
+            "0| ",
+        ])?;
+        let cnf = Cnf::from_board(&board);
+
+        assert!(cnf.decode("s UNSATISFIABLE\n").is_err());
+        assert!(cnf.decode("v 1 x 0\n").is_err());
+
+        let solved = cnf.decode("s SATISFIABLE\nv -1 0\n")?;
+        assert!(solved.is_solved());
+
+        Ok(())
+    }
+}
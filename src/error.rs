@@ -1,3 +1,12 @@
 
-error_chain! {}    
+use crate::parse::ParseError;
 
+error_chain! {
+    errors {
+        // The board text isn't in the expected format
+        Parse(error: ParseError) {
+            description("unable to parse the board")
+            display("{}", error)
+        }
+    }
+}
@@ -0,0 +1,387 @@
+/////////////////////////////////////////////////////////////////////
+//
+// A C ABI, for calling the solver from other languages.
+//
+// ffi/communicator.h is generated from this file by cbindgen. After
+// changing anything here, regenerate it by running the tests with
+// COMMUNICATOR_UPDATE_HEADER=1.
+//
+// Conventions:
+// - Boards are opaque, created by communicator_board_new and released by
+//   communicator_board_free.
+// - Strings returned by the library are released by communicator_string_free.
+// - When a call fails, it returns NULL, -1, or an ERROR/INVALID value, and
+//   communicator_last_error describes what went wrong.
+// - Panics are caught here, and reported as errors. They never unwind into C.
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::time::{Duration, Instant};
+
+use crate::board::*;
+use crate::error::*;
+use crate::ship::*;
+use crate::solve::*;
+use crate::square::*;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+// A board, as seen from C
+pub struct CommunicatorBoard(Board);
+
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CommunicatorSquare {
+    Invalid = -1,
+    Unknown,
+    Water,
+    Any,
+    Dot,
+    LeftEnd,
+    RightEnd,
+    TopEnd,
+    BottomEnd,
+    VerticalMiddle,
+    HorizontalMiddle,
+    AnyMiddle,
+}
+
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CommunicatorOutcome {
+    Error = -1,
+    Solved,
+    Stalled,
+    TimedOut,
+    PassLimitReached,
+}
+
+// Where the board's fleet came from. See FleetSource.
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CommunicatorFleetSource {
+    Error = -1,
+    Given,
+    Standard,
+    Unknown,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CommunicatorSolveOptions {
+    // 0 for no limit
+    pub max_passes: u32,
+
+    // 0 for no limit
+    pub timeout_ms: u32,
+
+    // If the rules get stuck, search for the solution
+    pub search: bool,
+}
+
+/////////////////////////////////////////////////////////////////////
+//
+// Error handling
+
+fn set_last_error(message: &str) {
+    // Interior NULs would truncate the message anyway
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+fn error_message(error: &Error) -> String {
+    error.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(": ")
+}
+
+// Run `body`, turning errors and panics into `failed`, with the message
+// saved for communicator_last_error
+fn guard<T>(failed: T, body: impl FnOnce() -> Result<T>) -> T {
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(error)) => {
+            set_last_error(&error_message(&error));
+            failed
+        },
+        Err(_) => {
+            set_last_error("Internal error: the solver panicked");
+            failed
+        },
+    }
+}
+
+unsafe fn board_ref<'a>(board: *const CommunicatorBoard) -> Result<&'a Board> {
+    match board.as_ref() {
+        Some(board) => Ok(&board.0),
+        None        => bail!("The board is NULL"),
+    }
+}
+
+unsafe fn board_mut<'a>(board: *mut CommunicatorBoard) -> Result<&'a mut Board> {
+    match board.as_mut() {
+        Some(board) => Ok(&mut board.0),
+        None        => bail!("The board is NULL"),
+    }
+}
+
+// The message from the last call on this thread that failed, or NULL.
+// Owned by the library, and valid until the next call that fails.
+#[no_mangle]
+pub extern "C" fn communicator_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| {
+        last_error.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn communicator_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/////////////////////////////////////////////////////////////////////
+//
+// Boards
+
+// Parse a board from text, in the same format as the command line uses
+#[no_mangle]
+pub unsafe extern "C" fn communicator_board_new(text: *const c_char) -> *mut CommunicatorBoard {
+    guard(ptr::null_mut(), || {
+        ensure!(!text.is_null(), "The board text is NULL");
+        let text = CStr::from_ptr(text).to_str()
+            .chain_err(|| "The board text isn't UTF-8")?;
+        let lines = text.lines().collect::<Vec<_>>();
+        let board = Board::new(&lines)?;
+
+        Ok(Box::into_raw(Box::new(CommunicatorBoard(board))))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn communicator_board_free(board: *mut CommunicatorBoard) {
+    if !board.is_null() {
+        drop(Box::from_raw(board));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn communicator_board_num_rows(board: *const CommunicatorBoard) -> isize {
+    guard(-1, || Ok(board_ref(board)?.layout.num_rows as isize))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn communicator_board_num_cols(board: *const CommunicatorBoard) -> isize {
+    guard(-1, || Ok(board_ref(board)?.layout.num_cols as isize))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn communicator_board_square(board: *const CommunicatorBoard, row_num: usize, col_num: usize)
+    -> CommunicatorSquare {
+
+    guard(CommunicatorSquare::Invalid, || {
+        let board = board_ref(board)?;
+        ensure!(row_num < board.layout.num_rows && col_num < board.layout.num_cols,
+            "Row {}, col {} is off the board", row_num, col_num);
+
+        Ok(match board[board.layout.coord(col_num, row_num)] {
+            Square::Unknown => CommunicatorSquare::Unknown,
+            Square::Water   => CommunicatorSquare::Water,
+            Square::ShipSquare(ship_square) => match ship_square {
+                ShipSquare::Any              => CommunicatorSquare::Any,
+                ShipSquare::Dot              => CommunicatorSquare::Dot,
+                ShipSquare::LeftEnd          => CommunicatorSquare::LeftEnd,
+                ShipSquare::RightEnd         => CommunicatorSquare::RightEnd,
+                ShipSquare::TopEnd           => CommunicatorSquare::TopEnd,
+                ShipSquare::BottomEnd        => CommunicatorSquare::BottomEnd,
+                ShipSquare::VerticalMiddle   => CommunicatorSquare::VerticalMiddle,
+                ShipSquare::HorizontalMiddle => CommunicatorSquare::HorizontalMiddle,
+                ShipSquare::AnyMiddle        => CommunicatorSquare::AnyMiddle,
+            },
+        })
+    })
+}
+
+// How many ship squares are still to be found in a row
+#[no_mangle]
+pub unsafe extern "C" fn communicator_board_row_remaining(board: *const CommunicatorBoard, row_num: usize) -> isize {
+    guard(-1, || {
+        let board = board_ref(board)?;
+        ensure!(row_num < board.layout.num_rows, "Row {} is off the board", row_num);
+
+        Ok(board.ship_squares_remaining(board.layout.row(row_num)) as isize)
+    })
+}
+
+// How many ship squares are still to be found in a col
+#[no_mangle]
+pub unsafe extern "C" fn communicator_board_col_remaining(board: *const CommunicatorBoard, col_num: usize) -> isize {
+    guard(-1, || {
+        let board = board_ref(board)?;
+        ensure!(col_num < board.layout.num_cols, "Col {} is off the board", col_num);
+
+        Ok(board.ship_squares_remaining(board.layout.col(col_num)) as isize)
+    })
+}
+
+// How many ships of the given size are still to be found
+#[no_mangle]
+pub unsafe extern "C" fn communicator_board_ships_remaining(board: *const CommunicatorBoard, size: usize) -> isize {
+    guard(-1, || {
+        let board = board_ref(board)?;
+
+        Ok(board.num_remaining_ships_to_find(ExpectedShip { size }) as isize)
+    })
+}
+
+// Whether the fleet came from the ships: line, or was assumed
+#[no_mangle]
+pub unsafe extern "C" fn communicator_board_fleet_source(board: *const CommunicatorBoard) -> CommunicatorFleetSource {
+    guard(CommunicatorFleetSource::Error, || {
+        Ok(match board_ref(board)?.fleet_source() {
+            FleetSource::Given       => CommunicatorFleetSource::Given,
+            FleetSource::Standard(_) => CommunicatorFleetSource::Standard,
+            FleetSource::Unknown     => CommunicatorFleetSource::Unknown,
+        })
+    })
+}
+
+// The board as text, one line per row. Release it with communicator_string_free.
+#[no_mangle]
+pub unsafe extern "C" fn communicator_board_to_string(board: *const CommunicatorBoard) -> *mut c_char {
+    guard(ptr::null_mut(), || {
+        let text = board_ref(board)?.to_strings().join("\n");
+        let text = CString::new(text).chain_err(|| "The board text contains NUL")?;
+
+        Ok(text.into_raw())
+    })
+}
+
+/////////////////////////////////////////////////////////////////////
+//
+// Solving
+
+#[no_mangle]
+pub extern "C" fn communicator_solve_options_default() -> CommunicatorSolveOptions {
+    Default::default()
+}
+
+// Solve the board in place. `options` may be NULL, for the defaults.
+// If the solver stops early, the board is as far as it got.
+#[no_mangle]
+pub unsafe extern "C" fn communicator_solve(board: *mut CommunicatorBoard, options: *const CommunicatorSolveOptions)
+    -> CommunicatorOutcome {
+
+    guard(CommunicatorOutcome::Error, || {
+        let board = board_mut(board)?;
+        let options = options.as_ref().copied().unwrap_or_default();
+
+        let config = SolveConfig {
+            max_passes: Some(options.max_passes as usize).filter(|&max_passes| max_passes > 0),
+            deadline: Some(options.timeout_ms)
+                .filter(|&timeout_ms| timeout_ms > 0)
+                .map(|timeout_ms| Instant::now() + Duration::from_millis(u64::from(timeout_ms))),
+            ..Default::default()
+        };
+        let result = solve_with_config(board.clone(), config, &mut Quiet)?;
+        *board = result.board;
+
+        Ok(match result.outcome {
+            SolveOutcome::Stalled if options.search => {
+                if solve_by_search(board)? {
+                    CommunicatorOutcome::Solved
+                }
+                else {
+                    CommunicatorOutcome::Stalled
+                }
+            },
+            SolveOutcome::Solved           => CommunicatorOutcome::Solved,
+            SolveOutcome::Stalled          => CommunicatorOutcome::Stalled,
+            SolveOutcome::TimedOut         => CommunicatorOutcome::TimedOut,
+            SolveOutcome::PassLimitReached => CommunicatorOutcome::PassLimitReached,
+            SolveOutcome::Cancelled        => bail!("The solve was cancelled"),
+            SolveOutcome::Invalid(error) | SolveOutcome::Contradiction(error) => bail!(error),
+        })
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(communicator_last_error()) }.to_str().unwrap().to_string()
+    }
+
+    fn new_board(text: &str) -> *mut CommunicatorBoard {
+        let text = CString::new(text).unwrap();
+        unsafe { communicator_board_new(text.as_ptr()) }
+    }
+
+    #[test]
+    fn it_solves_through_the_abi() {
+        let board = new_board("ships: 2sq x 1.\n  01\n1|  \n0| v");
+        assert!(!board.is_null());
+
+        unsafe {
+            assert_eq!(communicator_board_num_rows(board), 2);
+            assert_eq!(communicator_board_num_cols(board), 2);
+            assert_eq!(communicator_board_square(board, 1, 1), CommunicatorSquare::BottomEnd);
+            assert_eq!(communicator_board_row_remaining(board, 0), 1);
+            assert_eq!(communicator_board_ships_remaining(board, 2), 1);
+            assert_eq!(communicator_board_fleet_source(board), CommunicatorFleetSource::Given);
+
+            assert_eq!(communicator_solve(board, ptr::null()), CommunicatorOutcome::Solved);
+            assert_eq!(communicator_board_square(board, 0, 1), CommunicatorSquare::TopEnd);
+            assert_eq!(communicator_board_col_remaining(board, 1), 0);
+
+            let text = communicator_board_to_string(board);
+            assert_eq!(CStr::from_ptr(text).to_str().unwrap(), "ships: 2sq x 0.\n  00\n0|~^\n0|~v");
+            communicator_string_free(text);
+
+            communicator_board_free(board);
+        }
+    }
+
+    #[test]
+    fn it_reports_errors() {
+        assert!(new_board("  12\n1| ").is_null());
+        assert!(!last_error().is_empty());
+
+        unsafe {
+            assert_eq!(communicator_board_num_rows(ptr::null()), -1);
+            assert_eq!(last_error(), "The board is NULL");
+
+            let board = new_board("  1\n1| ");
+            assert_eq!(communicator_board_square(board, 0, 5), CommunicatorSquare::Invalid);
+            assert_eq!(last_error(), "Row 0, col 5 is off the board");
+            communicator_board_free(board);
+        }
+    }
+
+    #[test]
+    fn it_applies_solve_options() {
+        let board = new_board("ships: 1sq x 2.\n  101\n1|   \n0|   \n1|   ");
+        let mut options = communicator_solve_options_default();
+
+        unsafe {
+            options.max_passes = 1;
+            assert_eq!(communicator_solve(board, &options), CommunicatorOutcome::PassLimitReached);
+
+            options.max_passes = 0;
+            assert_eq!(communicator_solve(board, &options), CommunicatorOutcome::Stalled);
+
+            options.search = true;
+            assert_eq!(communicator_solve(board, &options), CommunicatorOutcome::Solved);
+
+            communicator_board_free(board);
+        }
+    }
+}
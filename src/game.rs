@@ -0,0 +1,580 @@
+/////////////////////////////////////////////////////////////////////
+//
+// Classic two-player Battleship.
+//
+// Each player hides a fleet on their own ocean grid, then the players take
+// turns firing at each other's grids. The first player to sink the
+// other's whole fleet wins.
+//
+// Fleets follow the same rules as the puzzles: ships are straight lines,
+// and no two ships may touch, not even diagonally.
+
+use std::ops::Index;
+
+use crate::error::*;
+use crate::layout::*;
+use crate::ship::*;
+use crate::square::*;
+
+mod ai;
+mod client;
+mod commitment;
+mod protocol;
+mod puzzle;
+mod server;
+mod tournament;
+
+pub use self::ai::*;
+pub use self::client::*;
+pub use self::commitment::*;
+pub use self::protocol::*;
+pub use self::puzzle::*;
+pub use self::server::*;
+pub use self::tournament::*;
+
+// The fleet from the classic board game: a carrier, a battleship, a cruiser,
+// a submarine, and a destroyer. Played on a 10x10 grid.
+pub fn standard_fleet() -> Vec<ExpectedShip> {
+    [5, 4, 3, 3, 2].iter()
+        .map(|&size| ExpectedShip { size })
+        .collect()
+}
+
+pub fn standard_layout() -> Layout {
+    Layout { num_rows: 10, num_cols: 10 }
+}
+
+/////////////////////////////////////////////////////////////////////
+//
+// Placing ships
+
+// Where a player put one of their ships. Unlike Ship, this doesn't borrow a
+// Layout, so a player can hold on to it for the whole game.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Placement {
+    pub col_num: usize,
+    pub row_num: usize,
+    pub incrementing_axis: Axis,
+    pub size: usize,
+}
+
+impl Placement {
+    pub fn new(col_num: usize, row_num: usize, incrementing_axis: Axis, size: usize) -> Self {
+        Placement { col_num, row_num, incrementing_axis, size }
+    }
+
+    pub(crate) fn from_ship(ship: Ship) -> Self {
+        Placement {
+            col_num: ship.head.origin.col_num,
+            row_num: ship.head.origin.row_num,
+            incrementing_axis: ship.head.incrementing_axis,
+            size: ship.size,
+        }
+    }
+
+    pub(crate) fn to_ship<'a>(self, layout: &'a Layout) -> Ship<'a> {
+        Ship::new(layout.coord(self.col_num, self.row_num), self.incrementing_axis, self.size)
+    }
+
+    // Is every square of the ship on the board?
+    fn is_on_board(self, layout: &Layout) -> bool {
+        self.size > 0
+            && self.col_num < layout.num_cols && self.row_num < layout.num_rows
+            && self.to_ship(layout).is_in_bounds()
+    }
+
+    // Does this ship cover the square at (col_num, row_num)? Doesn't check bounds.
+    pub fn covers(self, col_num: usize, row_num: usize) -> bool {
+        match self.incrementing_axis {
+            Axis::Row => col_num == self.col_num
+                && row_num >= self.row_num && row_num < self.row_num + self.size,
+            Axis::Col => row_num == self.row_num
+                && col_num >= self.col_num && col_num < self.col_num + self.size,
+        }
+    }
+}
+
+// Check that the placements are a legal hiding place for the fleet: one
+// placement for each ship, all on the board, and no two ships touching.
+pub fn validate_fleet(layout: &Layout, fleet: &[ExpectedShip], placements: &[Placement]) -> Result<()> {
+    let mut expected_sizes = fleet.iter().map(|ship| ship.size).collect::<Vec<_>>();
+    let mut actual_sizes = placements.iter().map(|placement| placement.size).collect::<Vec<_>>();
+    expected_sizes.sort();
+    actual_sizes.sort();
+    ensure!(expected_sizes == actual_sizes,
+        "Fleet should have ships of sizes {:?}, but has ships of sizes {:?}",
+        expected_sizes, actual_sizes);
+
+    for placement in placements.iter() {
+        ensure!(placement.size > 0, "Ships must have at least one square");
+        ensure!(placement.is_on_board(layout),
+            "Ship of size {} at row {}, col {} goes off the board",
+            placement.size, placement.row_num, placement.col_num);
+    }
+
+    // The squares around a ship must be water, just like in the puzzles
+    for (ship_idx, placement) in placements.iter().enumerate() {
+        let ship = placement.to_ship(layout);
+
+        for (square_idx, coord) in ship.coords().unwrap().enumerate() {
+            let neighbors = ship.expected_square_for_idx(square_idx)
+                .water_neighbors()
+                .into_iter()
+                .filter_map(|neighbor| coord.neighbor(neighbor))
+                .chain(std::iter::once(coord));
+
+            for neighbor in neighbors {
+                let other = placements.iter()
+                    .enumerate()
+                    .find(|&(other_idx, other)| other_idx != ship_idx
+                        && other.covers(neighbor.col_num, neighbor.row_num));
+
+                if let Some((_, other)) = other {
+                    bail!("Ship at row {}, col {} touches ship at row {}, col {}",
+                        placement.row_num, placement.col_num, other.row_num, other.col_num);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/////////////////////////////////////////////////////////////////////
+//
+// Grids
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ShotResult {
+    Miss,
+    Hit,
+    Sunk(Placement), // The whole ship is revealed once it's sunk
+}
+
+// A player's own grid: where their ships are, and where they've been shot at
+#[derive(Debug, Clone)]
+pub struct OceanGrid {
+    layout: Layout,
+    placements: Vec<Placement>,
+    hits_per_ship: Vec<usize>,
+    shot_at: Vec<Vec<bool>>,
+}
+
+impl OceanGrid {
+    fn new(layout: Layout, placements: Vec<Placement>) -> Self {
+        OceanGrid {
+            layout,
+            hits_per_ship: vec![0; placements.len()],
+            placements,
+            shot_at: vec![vec![false; layout.num_cols]; layout.num_rows],
+        }
+    }
+
+    pub fn was_shot_at(&self, col_num: usize, row_num: usize) -> bool {
+        self.shot_at[row_num][col_num]
+    }
+
+    pub fn num_ships_afloat(&self) -> usize {
+        self.placements.iter()
+            .zip(self.hits_per_ship.iter())
+            .filter(|(placement, &hits)| hits < placement.size)
+            .count()
+    }
+
+    fn receive_shot(&mut self, col_num: usize, row_num: usize) -> Result<ShotResult> {
+        ensure!(col_num < self.layout.num_cols && row_num < self.layout.num_rows,
+            "Shot at row {}, col {} is off the board", row_num, col_num);
+        ensure!(!self.shot_at[row_num][col_num],
+            "Row {}, col {} has already been shot at", row_num, col_num);
+
+        self.shot_at[row_num][col_num] = true;
+
+        let ship_idx = self.placements.iter()
+            .position(|placement| placement.covers(col_num, row_num));
+
+        let result = match ship_idx {
+            None => ShotResult::Miss,
+            Some(ship_idx) => {
+                self.hits_per_ship[ship_idx] += 1;
+
+                let placement = self.placements[ship_idx];
+                if self.hits_per_ship[ship_idx] == placement.size {
+                    ShotResult::Sunk(placement)
+                }
+                else {
+                    ShotResult::Hit
+                }
+            },
+        };
+
+        Ok(result)
+    }
+
+    // Ships are drawn with the same characters as in the puzzles. Squares of a
+    // ship that have been hit are 'X', and shots that missed are '~'.
+    pub fn to_strings(&self) -> Vec<String> {
+        (0 .. self.layout.num_rows)
+            .map(|row_num| {
+                (0 .. self.layout.num_cols)
+                    .map(|col_num| {
+                        let placement = self.placements.iter()
+                            .find(|placement| placement.covers(col_num, row_num));
+                        let shot_at = self.shot_at[row_num][col_num];
+
+                        match (placement, shot_at) {
+                            (None,    false) => ' ',
+                            (None,    true)  => '~',
+                            (Some(_), true)  => 'X',
+                            (Some(placement), false) => {
+                                let ship = placement.to_ship(&self.layout);
+                                let square_idx = (col_num - placement.col_num) + (row_num - placement.row_num);
+                                let square = Square::ShipSquare(ship.expected_square_for_idx(square_idx));
+                                square.to_string().chars().next().unwrap()
+                            },
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+// What a player knows about their opponent's grid. Unknown until shot at,
+// then water for a miss, a ship square for a hit. Once a ship is sunk, its
+// squares show its shape and the squares around it become water.
+#[derive(Debug, Clone)]
+pub struct TargetGrid {
+    pub layout: Layout,
+    squares: Vec<Vec<Square>>,
+}
+
+impl TargetGrid {
+    fn new(layout: Layout) -> Self {
+        TargetGrid {
+            layout,
+            squares: vec![vec![Square::Unknown; layout.num_cols]; layout.num_rows],
+        }
+    }
+
+    // Errors if the shot, or the ship it sank, is off the board. The result
+    // may come from the other end of a connection, so it can't be trusted.
+    fn record(&mut self, col_num: usize, row_num: usize, result: ShotResult) -> Result<()> {
+        let layout = self.layout;
+        ensure!(col_num < layout.num_cols && row_num < layout.num_rows,
+            "Shot at row {}, col {} is off the board", row_num, col_num);
+
+        match result {
+            ShotResult::Miss => self.squares[row_num][col_num] = Square::Water,
+            ShotResult::Hit  => self.squares[row_num][col_num] = Square::ShipSquare(ShipSquare::Any),
+            ShotResult::Sunk(placement) => {
+                ensure!(placement.is_on_board(&layout),
+                    "Sunk ship of size {} at row {}, col {} goes off the board",
+                    placement.size, placement.row_num, placement.col_num);
+
+                let ship = placement.to_ship(&layout);
+
+                for (square_idx, coord) in ship.coords().unwrap().enumerate() {
+                    let ship_square = ship.expected_square_for_idx(square_idx);
+                    self.squares[coord.row_num][coord.col_num] = Square::ShipSquare(ship_square);
+
+                    for neighbor in ship_square.water_neighbors() {
+                        if let Some(neighbor) = coord.neighbor(neighbor) {
+                            self.squares[neighbor.row_num][neighbor.col_num] = Square::Water;
+                        }
+                    }
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    pub fn to_strings(&self) -> Vec<String> {
+        self.squares.iter()
+            .map(|row| row.iter().map(Square::to_string).collect())
+            .collect()
+    }
+}
+
+impl<'coord> Index<Coord<'coord>> for TargetGrid {
+    type Output = Square;
+
+    fn index(&self, index: Coord) -> &Square {
+        &self.squares[index.row_num][index.col_num]
+    }
+}
+
+/////////////////////////////////////////////////////////////////////
+//
+// The game
+
+#[derive(Debug, Clone)]
+struct Player {
+    ocean: OceanGrid,
+    target: TargetGrid,
+}
+
+#[derive(Debug, Clone)]
+pub struct Game {
+    layout: Layout,
+    fleet: Vec<ExpectedShip>,
+    players: [Player; 2],
+    current_player: usize,
+    winner: Option<usize>,
+}
+
+impl Game {
+    // Player 0 goes first
+    pub fn new(layout: Layout, fleet: &[ExpectedShip], placements: [Vec<Placement>; 2]) -> Result<Self> {
+        ensure!(!fleet.is_empty(), "A game needs at least one ship per player");
+
+        let new_player = |player_num: usize, placements: Vec<Placement>| -> Result<Player> {
+            validate_fleet(&layout, fleet, &placements)
+                .chain_err(|| format!("Player {} has an invalid fleet", player_num))?;
+
+            Ok(Player {
+                ocean: OceanGrid::new(layout, placements),
+                target: TargetGrid::new(layout),
+            })
+        };
+
+        let [placements_0, placements_1] = placements;
+        let players = [new_player(0, placements_0)?, new_player(1, placements_1)?];
+
+        Ok(Game {
+            layout,
+            fleet: fleet.to_vec(),
+            players,
+            current_player: 0,
+            winner: None,
+        })
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    pub fn fleet(&self) -> &[ExpectedShip] {
+        &self.fleet
+    }
+
+    // Whose turn it is
+    pub fn current_player(&self) -> usize {
+        self.current_player
+    }
+
+    pub fn winner(&self) -> Option<usize> {
+        self.winner
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner.is_some()
+    }
+
+    pub fn ocean(&self, player_num: usize) -> &OceanGrid {
+        &self.players[player_num].ocean
+    }
+
+    pub fn target(&self, player_num: usize) -> &TargetGrid {
+        &self.players[player_num].target
+    }
+
+    // How many shots the player has fired at their opponent
+    pub fn shots_taken(&self, player_num: usize) -> usize {
+        self.players[1 - player_num].ocean.shot_at.iter()
+            .flatten()
+            .filter(|&&shot_at| shot_at)
+            .count()
+    }
+
+    // The current player fires at their opponent. Play passes to the opponent
+    // unless this shot wins the game.
+    pub fn fire(&mut self, col_num: usize, row_num: usize) -> Result<ShotResult> {
+        ensure!(!self.is_over(), "The game is over");
+
+        let shooter = self.current_player;
+        let opponent = 1 - shooter;
+
+        let result = self.players[opponent].ocean.receive_shot(col_num, row_num)?;
+        self.players[shooter].target.record(col_num, row_num, result)?;
+
+        if self.players[opponent].ocean.num_ships_afloat() == 0 {
+            self.winner = Some(shooter);
+        }
+        else {
+            self.current_player = opponent;
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn small_layout() -> Layout {
+        Layout { num_rows: 4, num_cols: 4 }
+    }
+
+    fn small_fleet() -> Vec<ExpectedShip> {
+        vec![ExpectedShip { size: 2 }, ExpectedShip { size: 1 }]
+    }
+
+    //  0123
+    // 0<>
+    // 1
+    // 2   •
+    fn small_placements() -> Vec<Placement> {
+        vec![
+            Placement::new(0, 0, Axis::Col, 2),
+            Placement::new(3, 2, Axis::Row, 1),
+        ]
+    }
+
+    fn small_game() -> Result<Game> {
+        Game::new(small_layout(), &small_fleet(), [small_placements(), small_placements()])
+    }
+
+    #[test]
+    fn it_accepts_standard_fleet() -> Result<()> {
+        let placements = vec![
+            Placement::new(0, 0, Axis::Col, 5),
+            Placement::new(0, 2, Axis::Col, 4),
+            Placement::new(0, 4, Axis::Row, 3),
+            Placement::new(9, 0, Axis::Row, 3),
+            Placement::new(5, 9, Axis::Col, 2),
+        ];
+
+        validate_fleet(&standard_layout(), &standard_fleet(), &placements)
+    }
+
+    #[test]
+    fn it_rejects_touching_ships() {
+        let touching_sides = vec![
+            Placement::new(0, 0, Axis::Col, 2),
+            Placement::new(0, 1, Axis::Row, 1),
+        ];
+        assert!(validate_fleet(&small_layout(), &small_fleet(), &touching_sides).is_err());
+
+        let touching_corners = vec![
+            Placement::new(0, 0, Axis::Col, 2),
+            Placement::new(2, 1, Axis::Row, 1),
+        ];
+        assert!(validate_fleet(&small_layout(), &small_fleet(), &touching_corners).is_err());
+
+        let overlapping = vec![
+            Placement::new(0, 0, Axis::Col, 2),
+            Placement::new(1, 0, Axis::Row, 1),
+        ];
+        assert!(validate_fleet(&small_layout(), &small_fleet(), &overlapping).is_err());
+    }
+
+    #[test]
+    fn it_rejects_wrong_ships() {
+        let off_board = vec![
+            Placement::new(3, 0, Axis::Col, 2),
+            Placement::new(0, 2, Axis::Row, 1),
+        ];
+        assert!(validate_fleet(&small_layout(), &small_fleet(), &off_board).is_err());
+
+        let wrong_sizes = vec![
+            Placement::new(0, 0, Axis::Col, 3),
+            Placement::new(0, 2, Axis::Row, 1),
+        ];
+        assert!(validate_fleet(&small_layout(), &small_fleet(), &wrong_sizes).is_err());
+
+        let missing_ship = vec![
+            Placement::new(0, 0, Axis::Col, 2),
+        ];
+        assert!(validate_fleet(&small_layout(), &small_fleet(), &missing_ship).is_err());
+    }
+
+    #[test]
+    fn it_reports_miss_hit_and_sunk() -> Result<()> {
+        let mut game = small_game()?;
+
+        assert_eq!(game.fire(3, 3)?, ShotResult::Miss);
+        game.fire(3, 3)?; // player 1's turn
+        assert_eq!(game.fire(0, 0)?, ShotResult::Hit);
+        game.fire(2, 2)?;
+        assert_eq!(game.fire(1, 0)?, ShotResult::Sunk(Placement::new(0, 0, Axis::Col, 2)));
+        assert_eq!((game.shots_taken(0), game.shots_taken(1)), (3, 2));
+
+        assert_eq!(game.target(0).to_strings(), vec![
+            "<>~ ",
+            "~~~ ",
+            "    ",
+            "   ~",
+        ]);
+        assert_eq!(game.ocean(1).to_strings(), vec![
+            "XX  ",
+            "    ",
+            "   •",
+            "   ~",
+        ]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_results_off_the_board() -> Result<()> {
+        let mut target = TargetGrid::new(small_layout());
+
+        assert!(target.record(4, 0, ShotResult::Miss).is_err());
+        assert!(target.record(0, 4, ShotResult::Hit).is_err());
+        assert!(target.record(3, 0, ShotResult::Sunk(Placement::new(3, 0, Axis::Col, 2))).is_err());
+        assert!(target.record(0, 0, ShotResult::Sunk(Placement::new(0, 0, Axis::Col, 0))).is_err());
+
+        target.record(0, 0, ShotResult::Sunk(Placement::new(0, 0, Axis::Col, 2)))?;
+        assert_eq!(target.to_strings()[0], "<>~ ");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_takes_turns() -> Result<()> {
+        let mut game = small_game()?;
+
+        assert_eq!(game.current_player(), 0);
+        game.fire(3, 3)?;
+        assert_eq!(game.current_player(), 1);
+        game.fire(3, 3)?;
+        assert_eq!(game.current_player(), 0);
+
+        // Firing at the same square again isn't allowed, and doesn't use up the turn
+        assert!(game.fire(3, 3).is_err());
+        assert_eq!(game.current_player(), 0);
+
+        assert!(game.fire(4, 0).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_ends_when_fleet_is_sunk() -> Result<()> {
+        let mut game = small_game()?;
+
+        game.fire(0, 0)?;
+        game.fire(3, 3)?;
+        game.fire(1, 0)?;
+        game.fire(2, 3)?;
+        assert!(!game.is_over());
+        assert_eq!(game.fire(3, 2)?, ShotResult::Sunk(Placement::new(3, 2, Axis::Row, 1)));
+
+        assert!(game.is_over());
+        assert_eq!(game.winner(), Some(0));
+        assert_eq!(game.ocean(1).num_ships_afloat(), 0);
+        assert!(game.fire(0, 3).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_invalid_fleet_for_either_player() {
+        let bad = vec![Placement::new(0, 0, Axis::Col, 2)];
+
+        assert!(Game::new(small_layout(), &small_fleet(), [bad.clone(), small_placements()]).is_err());
+        assert!(Game::new(small_layout(), &small_fleet(), [small_placements(), bad]).is_err());
+    }
+}
@@ -0,0 +1,449 @@
+/////////////////////////////////////////////////////////////////////
+//
+// A computer opponent.
+//
+// The AI has two modes:
+// - Hunting: no ship has been hit without being sunk. Look for a new ship.
+// - Targeting: a ship has been hit but not sunk. Finish it off.
+//
+// At full strength, both modes use probability density. Every place that
+// an unsunk ship could still be hiding covers some squares. Fire at the
+// square that's covered by the most places.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use super::*;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Difficulty {
+    // Hunt by firing at random. Target by firing next to hits.
+    Random,
+    // Hunt on a checkerboard, spaced for the smallest unsunk ship. Target by density.
+    Parity,
+    // Hunt and target by density
+    Density,
+}
+
+impl Difficulty {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "random"  => Ok(Difficulty::Random),
+            "parity"  => Ok(Difficulty::Parity),
+            "density" => Ok(Difficulty::Density),
+            _         => bail!("Unknown difficulty: {}. Expected random, parity, or density.", name),
+        }
+    }
+}
+
+pub struct Ai {
+    difficulty: Difficulty,
+    rng: StdRng,
+}
+
+impl Ai {
+    // The seed makes games repeatable
+    pub fn new(difficulty: Difficulty, seed: u64) -> Self {
+        Ai {
+            difficulty,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    // Choose where to fire next, as (col_num, row_num)
+    pub fn choose_shot(&mut self, target: &TargetGrid, fleet: &[ExpectedShip]) -> Result<(usize, usize)> {
+        let layout = target.layout;
+        let unsunk = unsunk_ships(target, fleet);
+
+        let unknown = layout.all_coordinates()
+            .filter(|&coord| target[coord] == Square::Unknown)
+            .map(|coord| (coord.col_num, coord.row_num))
+            .collect::<Vec<_>>();
+        ensure!(!unknown.is_empty(), "Every square has already been shot at");
+
+        let has_hits = layout.all_coordinates()
+            .any(|coord| target[coord] == Square::ShipSquare(ShipSquare::Any));
+
+        let candidates = match (self.difficulty, has_hits) {
+            (Difficulty::Random, false) => unknown,
+            (Difficulty::Random, true)  => next_to_hits(target),
+
+            (Difficulty::Parity, false) => {
+                let spacing = unsunk.iter().map(|ship| ship.size).min().unwrap_or(1);
+                let on_parity = unknown.iter()
+                    .cloned()
+                    .filter(|&(col_num, row_num)| (col_num + row_num) % spacing == 0)
+                    .collect::<Vec<_>>();
+
+                if on_parity.is_empty() { unknown } else { on_parity }
+            },
+
+            (_, has_hits) => densest_squares(target, &unsunk, has_hits),
+        };
+
+        // Fall back to any unknown square if the grid doesn't make sense for
+        // the fleet, e.g. because it's a custom fleet the AI wasn't told about
+        let candidates = if candidates.is_empty() {
+            layout.all_coordinates()
+                .filter(|&coord| target[coord] == Square::Unknown)
+                .map(|coord| (coord.col_num, coord.row_num))
+                .collect()
+        }
+        else {
+            candidates
+        };
+
+        Ok(*candidates.choose(&mut self.rng).unwrap())
+    }
+}
+
+// The ships in the fleet that haven't been sunk yet. A sunk ship shows its shape
+// on the target grid.
+pub fn unsunk_ships(target: &TargetGrid, fleet: &[ExpectedShip]) -> Vec<ExpectedShip> {
+    let layout = target.layout;
+    let mut unsunk = fleet.to_vec();
+
+    // Every sunk ship starts with a dot, a left end, or a top end
+    for coord in layout.all_coordinates() {
+        let incrementing_axis = match target[coord] {
+            Square::ShipSquare(ShipSquare::Dot)     => None,
+            Square::ShipSquare(ShipSquare::LeftEnd) => Some(Axis::Col),
+            Square::ShipSquare(ShipSquare::TopEnd)  => Some(Axis::Row),
+            _ => continue,
+        };
+
+        let size = match incrementing_axis {
+            None => 1,
+            Some(axis) => (1 ..)
+                .take_while(|&offset| coord.offset(offset, axis)
+                    .is_some_and(|next| target[next].is_ship() && target[next] != Square::ShipSquare(ShipSquare::Any)))
+                .count() + 1,
+        };
+
+        if let Some(idx) = unsunk.iter().position(|ship| ship.size == size) {
+            unsunk.remove(idx);
+        }
+    }
+
+    unsunk
+}
+
+// Unknown squares directly next to a hit
+fn next_to_hits(target: &TargetGrid) -> Vec<(usize, usize)> {
+    use crate::neighbor::Neighbor::*;
+
+    let layout = target.layout;
+    let mut squares = layout.all_coordinates()
+        .filter(|&coord| target[coord] == Square::ShipSquare(ShipSquare::Any))
+        .flat_map(|coord| [N, S, E, W].iter()
+            .filter_map(|&neighbor| coord.neighbor(neighbor))
+            .collect::<Vec<_>>())
+        .filter(|&coord| target[coord] == Square::Unknown)
+        .map(|coord| (coord.col_num, coord.row_num))
+        .collect::<Vec<_>>();
+
+    squares.sort();
+    squares.dedup();
+    squares
+}
+
+// Could the ship be hiding here, given what's on the target grid?
+fn is_possible(target: &TargetGrid, ship: Ship) -> bool {
+    ship.coords().unwrap()
+        .enumerate()
+        .all(|(square_idx, coord)| {
+            let is_open = matches!(target[coord],
+                Square::Unknown | Square::ShipSquare(ShipSquare::Any));
+
+            // Ships can't touch, so there can't be another ship next to this one
+            let is_clear = ship.expected_square_for_idx(square_idx)
+                .water_neighbors()
+                .into_iter()
+                .filter_map(|neighbor| coord.neighbor(neighbor))
+                .all(|neighbor| !target[neighbor].is_ship());
+
+            is_open && is_clear
+        })
+}
+
+// Count how many possible ship placements cover each unknown square. Return the
+// unknown squares with the highest count. When targeting, only count placements
+// that go through a hit.
+fn densest_squares(target: &TargetGrid, unsunk: &[ExpectedShip], targeting: bool) -> Vec<(usize, usize)> {
+    let layout = target.layout;
+    let mut density = vec![vec![0; layout.num_cols]; layout.num_rows];
+
+    for &expected_ship in unsunk.iter() {
+        for ship_head in layout.possible_heads_for_ship(expected_ship) {
+            let ship = ship_head.to_ship(expected_ship);
+            if !is_possible(target, ship) {
+                continue;
+            }
+
+            let num_hits = ship.coords().unwrap()
+                .filter(|&coord| target[coord].is_ship())
+                .count();
+            if targeting && num_hits == 0 {
+                continue;
+            }
+
+            // Placements through more hits are much more likely
+            let weight = 1 + num_hits * 10;
+            for coord in ship.coords().unwrap() {
+                if target[coord] == Square::Unknown {
+                    density[coord.row_num][coord.col_num] += weight;
+                }
+            }
+        }
+    }
+
+    let max = density.iter().flatten().cloned().max().unwrap_or(0);
+    if max == 0 {
+        return Vec::new();
+    }
+
+    layout.all_coordinates()
+        .filter(|&coord| density[coord.row_num][coord.col_num] == max)
+        .map(|coord| (coord.col_num, coord.row_num))
+        .collect()
+}
+
+/////////////////////////////////////////////////////////////////////
+//
+// Placing a fleet
+
+// Hide the fleet at random, with no ships touching. Places the largest ships
+// first, since they're the hardest to fit.
+pub fn random_placements(layout: &Layout, fleet: &[ExpectedShip], rng: &mut impl Rng) -> Result<Vec<Placement>> {
+    let mut sorted_fleet = fleet.to_vec();
+    sorted_fleet.sort_by(|a, b| b.cmp(a));
+
+    // Crowded boards can paint us into a corner, so start over a few times if needed
+    for _ in 0 .. 100 {
+        let mut placements = Vec::<Placement>::new();
+
+        for &expected_ship in sorted_fleet.iter() {
+            let heads = layout.possible_heads_for_ship(expected_ship)
+                .map(|ship_head| Placement::from_ship(ship_head.to_ship(expected_ship)))
+                .filter(|&placement| {
+                    let mut candidate = placements.clone();
+                    candidate.push(placement);
+                    is_clear_of_others(layout, &candidate)
+                })
+                .collect::<Vec<_>>();
+
+            match heads.choose(rng) {
+                Some(&placement) => placements.push(placement),
+                None             => break,
+            }
+        }
+
+        if placements.len() == fleet.len() {
+            return Ok(placements);
+        }
+    }
+
+    bail!("Unable to fit a fleet of {} ships on a {}x{} board",
+        fleet.len(), layout.num_cols, layout.num_rows)
+}
+
+// Does the last placement stay clear of all the others?
+fn is_clear_of_others(layout: &Layout, placements: &[Placement]) -> bool {
+    let (last, others) = placements.split_last().unwrap();
+    let ship = last.to_ship(layout);
+
+    let is_clear = ship.coords().unwrap()
+        .enumerate()
+        .all(|(square_idx, coord)| {
+            ship.expected_square_for_idx(square_idx)
+                .water_neighbors()
+                .into_iter()
+                .filter_map(|neighbor| coord.neighbor(neighbor))
+                .chain(std::iter::once(coord))
+                .all(|neighbor| !others.iter().any(|other| other.covers(neighbor.col_num, neighbor.row_num)))
+        });
+
+    is_clear
+}
+
+/////////////////////////////////////////////////////////////////////
+//
+// Simulation
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationReport {
+    pub num_games: usize,
+    pub wins: [usize; 2],
+
+    // Number of shots the winner fired, averaged over all games
+    pub average_shots_to_win: f64,
+}
+
+// Play one game between two AIs, with fleets hidden at random
+pub fn play_ai_game(difficulties: [Difficulty; 2], layout: Layout, fleet: &[ExpectedShip],
+    rng: &mut impl Rng) -> Result<Game> {
+
+    let placements = [
+        random_placements(&layout, fleet, rng)?,
+        random_placements(&layout, fleet, rng)?,
+    ];
+    let mut game = Game::new(layout, fleet, placements)?;
+    let mut ais = [
+        Ai::new(difficulties[0], rng.gen()),
+        Ai::new(difficulties[1], rng.gen()),
+    ];
+
+    while !game.is_over() {
+        let player_num = game.current_player();
+        let (col_num, row_num) = ais[player_num].choose_shot(game.target(player_num), game.fleet())?;
+        game.fire(col_num, row_num)?;
+    }
+
+    Ok(game)
+}
+
+// Play AIs against each other on random fleets
+pub fn simulate(difficulties: [Difficulty; 2], layout: Layout, fleet: &[ExpectedShip],
+    num_games: usize, seed: u64) -> Result<SimulationReport> {
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut wins = [0, 0];
+    let mut total_shots = 0;
+
+    for _ in 0 .. num_games {
+        let game = play_ai_game(difficulties, layout, fleet, &mut rng)?;
+
+        let winner = game.winner().unwrap();
+        wins[winner] += 1;
+        total_shots += game.shots_taken(winner);
+    }
+
+    Ok(SimulationReport {
+        num_games,
+        wins,
+        average_shots_to_win: if num_games == 0 { 0.0 } else { total_shots as f64 / num_games as f64 },
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fire_all(game: &mut Game, shots: &[(usize, usize)]) -> Result<()> {
+        for &(col_num, row_num) in shots {
+            // Player 1 wastes their turns in the bottom row
+            game.fire(col_num, row_num)?;
+            if !game.is_over() {
+                let col_num = (0 .. game.layout().num_cols)
+                    .find(|&col_num| !game.ocean(0).was_shot_at(col_num, game.layout().num_rows - 1))
+                    .unwrap();
+                game.fire(col_num, game.layout().num_rows - 1)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn small_game() -> Result<Game> {
+        //  01234
+        // 0<-->
+        // 1
+        // 2    ^
+        // 3    v
+        // 4
+        let layout = Layout { num_rows: 5, num_cols: 5 };
+        let fleet = vec![ExpectedShip { size: 4 }, ExpectedShip { size: 2 }];
+        let placements = vec![
+            Placement::new(0, 0, Axis::Col, 4),
+            Placement::new(4, 2, Axis::Row, 2),
+        ];
+
+        Game::new(layout, &fleet, [placements.clone(), placements])
+    }
+
+    #[test]
+    fn it_finds_unsunk_ships() -> Result<()> {
+        let mut game = small_game()?;
+        fire_all(&mut game, &[(4, 2), (4, 3), (0, 0)])?;
+
+        let unsunk = unsunk_ships(game.target(0), game.fleet());
+        assert_eq!(unsunk, vec![ExpectedShip { size: 4 }]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_targets_around_hits() -> Result<()> {
+        let mut game = small_game()?;
+        fire_all(&mut game, &[(4, 2), (4, 3), (1, 0)])?;
+
+        // (1, 0) is a hit on the 4 square ship, so every difficulty fires along
+        // its row or col
+        for difficulty in [Difficulty::Random, Difficulty::Parity, Difficulty::Density].iter() {
+            let mut ai = Ai::new(*difficulty, 1);
+            let shot = ai.choose_shot(game.target(0), game.fleet())?;
+
+            let (col_num, row_num) = shot;
+            assert!(col_num == 1 || row_num == 0, "{:?} fired at {:?}", difficulty, shot);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_hunts_where_ships_fit() -> Result<()> {
+        let game = small_game()?;
+
+        // On an empty 5x5 grid, squares near the middle are covered by the most placements
+        let mut ai = Ai::new(Difficulty::Density, 1);
+        let (col_num, row_num) = ai.choose_shot(game.target(0), game.fleet())?;
+        assert!((1 ..= 3).contains(&col_num) && (1 ..= 3).contains(&row_num));
+
+        // Parity hunting only fires at every other square, because the smallest ship has size 2
+        let mut ai = Ai::new(Difficulty::Parity, 1);
+        for _ in 0 .. 10 {
+            let (col_num, row_num) = ai.choose_shot(game.target(0), game.fleet())?;
+            assert_eq!((col_num + row_num) % 2, 0);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_places_random_fleets() -> Result<()> {
+        let mut rng = StdRng::seed_from_u64(7);
+        let layout = standard_layout();
+        let fleet = standard_fleet();
+
+        for _ in 0 .. 20 {
+            let placements = random_placements(&layout, &fleet, &mut rng)?;
+            validate_fleet(&layout, &fleet, &placements)?;
+        }
+
+        // Too many ships to fit
+        let crowded = vec![ExpectedShip { size: 3 }; 10];
+        assert!(random_placements(&Layout { num_rows: 4, num_cols: 4 }, &crowded, &mut rng).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn density_beats_random() -> Result<()> {
+        let layout = standard_layout();
+        let fleet = standard_fleet();
+
+        let random  = simulate([Difficulty::Random,  Difficulty::Random],  layout, &fleet, 10, 42)?;
+        let density = simulate([Difficulty::Density, Difficulty::Density], layout, &fleet, 10, 42)?;
+        assert_eq!(density.num_games, 10);
+        assert_eq!(density.wins[0] + density.wins[1], 10);
+
+        assert!(density.average_shots_to_win < random.average_shots_to_win,
+            "density: {:?}, random: {:?}", density, random);
+
+        let vs = simulate([Difficulty::Density, Difficulty::Random], layout, &fleet, 10, 42)?;
+        assert!(vs.wins[0] > vs.wins[1], "{:?}", vs);
+
+        Ok(())
+    }
+}
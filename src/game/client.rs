@@ -0,0 +1,61 @@
+/////////////////////////////////////////////////////////////////////
+//
+// A reference client: connects to a server and lets the built-in AI play.
+
+use std::net::TcpStream;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use super::*;
+
+// Play one match. Returns true if the AI won.
+pub fn play_as_ai(address: &str, name: &str, difficulty: Difficulty, seed: u64) -> Result<bool> {
+    let stream = TcpStream::connect(address)
+        .chain_err(|| format!("Unable to connect to {}", address))?;
+    let mut connection = Connection::new(stream)?;
+
+    connection.send(&Message::Hello { name: name.to_string() })?;
+    let (layout, fleet, hidden_fleets) = match connection.receive()? {
+        Message::Welcome { layout, fleet, hidden_fleets, .. } => (layout, fleet, hidden_fleets),
+        message => bail!("Expected WELCOME, but got {}", message),
+    };
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let placements = random_placements(&layout, &fleet, &mut rng)?;
+    let salt = random_salt(&mut rng);
+    if hidden_fleets {
+        connection.send(&Message::Commit(Commitment::new(&placements, &salt)))?;
+    }
+    else {
+        connection.send(&Message::Fleet(placements.clone()))?;
+    }
+    match connection.receive()? {
+        Message::FleetOk => {},
+        message => bail!("Expected FLEET-OK, but got {}", message),
+    }
+
+    let mut ai = Ai::new(difficulty, seed);
+    let mut target = TargetGrid::new(layout);
+    let mut ocean = OceanGrid::new(layout, placements.clone());
+
+    loop {
+        match connection.receive()? {
+            Message::YourTurn => {
+                let (col_num, row_num) = ai.choose_shot(&target, &fleet)?;
+                connection.send(&Message::Fire { col_num, row_num })?;
+            },
+            Message::Result { col_num, row_num, result } => target.record(col_num, row_num, result)?,
+            Message::Incoming { .. } => {},
+            Message::Shot { col_num, row_num } => {
+                let result = ocean.receive_shot(col_num, row_num)?;
+                connection.send(&Message::Answer(result))?;
+            },
+            Message::RevealFleet => {
+                connection.send(&Message::Reveal { salt: salt.clone(), placements: placements.clone() })?;
+            },
+            Message::GameOver { won } => return Ok(won),
+            message => bail!("Unexpected message from server: {}", message),
+        }
+    }
+}
@@ -0,0 +1,182 @@
+/////////////////////////////////////////////////////////////////////
+//
+// Committing to a hidden fleet.
+//
+// When the players hide their own fleets, nothing stops a player from
+// moving a ship after it's been shot at. So before play starts, each player
+// publishes a commitment: a SHA-256 hash of a secret salt and their fleet.
+// The hash gives nothing away about the fleet, but once the game is over the
+// player has to reveal the fleet and salt, and they have to match the hash.
+// Then every answer the player gave about shots at their fleet can be
+// checked against the fleet they really had.
+//
+// The salt stops an opponent from hashing every possible fleet and looking
+// for one that matches.
+
+use std::fmt;
+
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+use super::*;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Commitment(pub [u8; 32]);
+
+impl Commitment {
+    pub fn new(placements: &[Placement], salt: &str) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(salt.as_bytes());
+        hasher.update(b":");
+        hasher.update(serialize_fleet(placements).as_bytes());
+
+        Commitment(hasher.finalize().into())
+    }
+
+    pub fn parse(hex: &str) -> Result<Self> {
+        ensure!(hex.len() == 64 && hex.is_ascii(), "Expected a 64 digit hex commitment, but got {}", hex);
+
+        let mut bytes = [0; 32];
+        for (byte_idx, byte) in bytes.iter_mut().enumerate() {
+            let digits = &hex[byte_idx * 2 .. byte_idx * 2 + 2];
+            *byte = u8::from_str_radix(digits, 16)
+                .chain_err(|| format!("Expected a hex commitment, but got {}", hex))?;
+        }
+
+        Ok(Commitment(bytes))
+    }
+}
+
+impl fmt::Display for Commitment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in self.0.iter() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+// Ships are written the same way as in the protocol, e.g. 0,3,H,5 9,2,V,3
+pub fn serialize_fleet(placements: &[Placement]) -> String {
+    placements.iter()
+        .map(|&placement| format_placement(placement))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn random_salt(rng: &mut impl Rng) -> String {
+    (0 .. 16)
+        .map(|_| format!("{:02x}", rng.gen::<u8>()))
+        .collect()
+}
+
+// A shot at a player's fleet, and what the player said it hit
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ShotRecord {
+    pub col_num: usize,
+    pub row_num: usize,
+    pub result: ShotResult,
+}
+
+// Check a revealed fleet: that it matches the commitment, that it's a legal
+// fleet, and that every answer the player gave was true.
+pub fn verify_fleet(commitment: Commitment, salt: &str, placements: &[Placement],
+    layout: Layout, fleet: &[ExpectedShip], shots: &[ShotRecord]) -> Result<()> {
+
+    ensure!(Commitment::new(placements, salt) == commitment,
+        "The revealed fleet doesn't match the commitment");
+    validate_fleet(&layout, fleet, placements)
+        .chain_err(|| "The revealed fleet is invalid")?;
+
+    let mut ocean = OceanGrid::new(layout, placements.to_vec());
+    for shot in shots.iter() {
+        let result = ocean.receive_shot(shot.col_num, shot.row_num)?;
+        ensure!(result == shot.result,
+            "The shot at row {}, col {} was answered {:?}, but the revealed fleet says {:?}",
+            shot.row_num, shot.col_num, shot.result, result);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn layout() -> Layout {
+        Layout { num_rows: 4, num_cols: 4 }
+    }
+
+    fn fleet() -> Vec<ExpectedShip> {
+        vec![ExpectedShip { size: 2 }, ExpectedShip { size: 1 }]
+    }
+
+    //  0123
+    // 0<>
+    // 1
+    // 2   •
+    fn placements() -> Vec<Placement> {
+        vec![
+            Placement::new(0, 0, Axis::Col, 2),
+            Placement::new(3, 2, Axis::Row, 1),
+        ]
+    }
+
+    fn shot(col_num: usize, row_num: usize, result: ShotResult) -> ShotRecord {
+        ShotRecord { col_num, row_num, result }
+    }
+
+    #[test]
+    fn it_round_trips_commitments() -> Result<()> {
+        let commitment = Commitment::new(&placements(), "salt");
+        assert_eq!(Commitment::parse(&commitment.to_string())?, commitment);
+
+        assert!(Commitment::parse("abc").is_err());
+        assert!(Commitment::parse(&"zz".repeat(32)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_depends_on_salt_and_fleet() {
+        let commitment = Commitment::new(&placements(), "salt");
+
+        assert_ne!(Commitment::new(&placements(), "pepper"), commitment);
+        assert_ne!(Commitment::new(&placements()[.. 1], "salt"), commitment);
+    }
+
+    #[test]
+    fn it_verifies_honest_answers() -> Result<()> {
+        let commitment = Commitment::new(&placements(), "salt");
+        let shots = vec![
+            shot(3, 3, ShotResult::Miss),
+            shot(0, 0, ShotResult::Hit),
+            shot(1, 0, ShotResult::Sunk(Placement::new(0, 0, Axis::Col, 2))),
+        ];
+
+        verify_fleet(commitment, "salt", &placements(), layout(), &fleet(), &shots)
+    }
+
+    #[test]
+    fn it_catches_cheaters() {
+        let commitment = Commitment::new(&placements(), "salt");
+        let verify = |salt: &str, placements: &[Placement], shots: &[ShotRecord]|
+            verify_fleet(commitment, salt, placements, layout(), &fleet(), shots);
+
+        // Lying about a hit
+        assert!(verify("salt", &placements(), &[shot(0, 0, ShotResult::Miss)]).is_err());
+
+        // Lying about a sinking
+        assert!(verify("salt", &placements(), &[shot(3, 2, ShotResult::Hit)]).is_err());
+
+        // Revealing a different fleet than the one committed to
+        let moved = vec![
+            Placement::new(0, 3, Axis::Col, 2),
+            Placement::new(3, 2, Axis::Row, 1),
+        ];
+        assert!(verify("salt", &moved, &[shot(0, 0, ShotResult::Miss)]).is_err());
+
+        // Revealing the wrong salt
+        assert!(verify("pepper", &placements(), &[]).is_err());
+    }
+}
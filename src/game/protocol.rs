@@ -0,0 +1,331 @@
+/////////////////////////////////////////////////////////////////////
+//
+// A line-based text protocol for playing Battleship over TCP.
+//
+// Each message is one line of space-separated words. Coordinates are
+// always col, then row. A ship is written as col,row,axis,size where the
+// axis is H (horizontal) or V (vertical), e.g. 0,3,H,5
+//
+// A match goes like this:
+//
+//   client -> server   HELLO <name>
+//   server -> client   WELCOME <player_num> <num_cols> <num_rows> <ship sizes, e.g. 5,4,3,3,2>
+//   client -> server   FLEET <ship> <ship> ...
+//   server -> client   FLEET-OK                       (or ERROR, and the client tries again)
+//
+// Then, until someone wins, the server tells one player it's their turn:
+//
+//   server -> client   YOUR-TURN
+//   client -> server   FIRE <col> <row>
+//   server -> client   RESULT <col> <row> MISS|HIT|SUNK <ship>    (or ERROR, and the client fires again)
+//   server -> other    INCOMING <col> <row> MISS|HIT|SUNK <ship>
+//
+// And finally:
+//
+//   server -> client   GAME-OVER WIN|LOSE
+//
+// In a hidden-fleet match, WELCOME ends with HIDDEN, and the server never
+// sees the fleets until the end. Each player commits to their fleet instead
+// (see commitment.rs), and answers shots at it themselves:
+//
+//   client -> server   COMMIT <hex hash>
+//   server -> client   FLEET-OK
+//   ...
+//   server -> other    SHOT <col> <row>
+//   other -> server    ANSWER MISS|HIT|SUNK <ship>
+//   server -> client   RESULT <col> <row> MISS|HIT|SUNK <ship>
+//
+// Once someone has sunk the whole fleet, both players reveal their fleets,
+// and a player whose answers don't match their revealed fleet loses:
+//
+//   server -> client   REVEAL-FLEET
+//   client -> server   REVEAL <salt> <ship> <ship> ...
+//   server -> client   GAME-OVER WIN|LOSE
+
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use super::*;
+
+pub const DEFAULT_PORT: u16 = 7878;
+
+// Give up on a player who goes quiet for this long
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Message {
+    Hello { name: String },
+    Welcome { player_num: usize, layout: Layout, fleet: Vec<ExpectedShip>, hidden_fleets: bool },
+    Fleet(Vec<Placement>),
+    Commit(Commitment),
+    FleetOk,
+    YourTurn,
+    Fire { col_num: usize, row_num: usize },
+    Result { col_num: usize, row_num: usize, result: ShotResult },
+    Incoming { col_num: usize, row_num: usize, result: ShotResult },
+    Shot { col_num: usize, row_num: usize },
+    Answer(ShotResult),
+    RevealFleet,
+    Reveal { salt: String, placements: Vec<Placement> },
+    GameOver { won: bool },
+    Error(String),
+}
+
+impl Message {
+    pub fn parse(line: &str) -> Result<Self> {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let (&keyword, args) = words.split_first()
+            .ok_or("Received an empty message")?;
+
+        let message = match (keyword, args) {
+            ("HELLO", [name]) => Message::Hello { name: name.to_string() },
+            ("WELCOME", [player_num, num_cols, num_rows, fleet, hidden @ ..])
+                if hidden.is_empty() || hidden == ["HIDDEN"] => Message::Welcome {
+                player_num: parse_num(player_num)?,
+                layout: Layout {
+                    num_cols: parse_num(num_cols)?,
+                    num_rows: parse_num(num_rows)?,
+                },
+                fleet: fleet.split(',')
+                    .map(|size| parse_num(size).map(ExpectedShip::from))
+                    .collect::<Result<_>>()?,
+                hidden_fleets: !hidden.is_empty(),
+            },
+            ("FLEET", ships) => Message::Fleet(parse_placements(ships)?),
+            ("COMMIT", [commitment]) => Message::Commit(Commitment::parse(commitment)?),
+            ("FLEET-OK", []) => Message::FleetOk,
+            ("YOUR-TURN", []) => Message::YourTurn,
+            ("FIRE", [col_num, row_num]) => Message::Fire {
+                col_num: parse_num(col_num)?,
+                row_num: parse_num(row_num)?,
+            },
+            ("RESULT", [col_num, row_num, result @ ..]) => Message::Result {
+                col_num: parse_num(col_num)?,
+                row_num: parse_num(row_num)?,
+                result: parse_result(result)?,
+            },
+            ("INCOMING", [col_num, row_num, result @ ..]) => Message::Incoming {
+                col_num: parse_num(col_num)?,
+                row_num: parse_num(row_num)?,
+                result: parse_result(result)?,
+            },
+            ("SHOT", [col_num, row_num]) => Message::Shot {
+                col_num: parse_num(col_num)?,
+                row_num: parse_num(row_num)?,
+            },
+            ("ANSWER", result) => Message::Answer(parse_result(result)?),
+            ("REVEAL-FLEET", []) => Message::RevealFleet,
+            ("REVEAL", [salt, ships @ ..]) => Message::Reveal {
+                salt: salt.to_string(),
+                placements: parse_placements(ships)?,
+            },
+            ("GAME-OVER", ["WIN"])  => Message::GameOver { won: true },
+            ("GAME-OVER", ["LOSE"]) => Message::GameOver { won: false },
+            ("ERROR", _) => Message::Error(args.join(" ")),
+            _ => bail!("Unable to understand message: {}", line.trim()),
+        };
+
+        Ok(message)
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Message::Hello { name } => write!(f, "HELLO {}", name),
+            Message::Welcome { player_num, layout, fleet, hidden_fleets } => {
+                let sizes = fleet.iter()
+                    .map(|ship| ship.size.to_string())
+                    .collect::<Vec<_>>();
+                write!(f, "WELCOME {} {} {} {}", player_num, layout.num_cols, layout.num_rows, sizes.join(","))?;
+                if *hidden_fleets {
+                    write!(f, " HIDDEN")?;
+                }
+                Ok(())
+            },
+            Message::Fleet(placements) => {
+                write!(f, "FLEET")?;
+                for placement in placements.iter() {
+                    write!(f, " {}", format_placement(*placement))?;
+                }
+                Ok(())
+            },
+            Message::Commit(commitment) => write!(f, "COMMIT {}", commitment),
+            Message::FleetOk  => write!(f, "FLEET-OK"),
+            Message::YourTurn => write!(f, "YOUR-TURN"),
+            Message::Fire { col_num, row_num } => write!(f, "FIRE {} {}", col_num, row_num),
+            Message::Result { col_num, row_num, result } =>
+                write!(f, "RESULT {} {} {}", col_num, row_num, format_result(*result)),
+            Message::Incoming { col_num, row_num, result } =>
+                write!(f, "INCOMING {} {} {}", col_num, row_num, format_result(*result)),
+            Message::Shot { col_num, row_num } => write!(f, "SHOT {} {}", col_num, row_num),
+            Message::Answer(result) => write!(f, "ANSWER {}", format_result(*result)),
+            Message::RevealFleet => write!(f, "REVEAL-FLEET"),
+            Message::Reveal { salt, placements } => {
+                write!(f, "REVEAL {}", salt)?;
+                for placement in placements.iter() {
+                    write!(f, " {}", format_placement(*placement))?;
+                }
+                Ok(())
+            },
+            Message::GameOver { won } => write!(f, "GAME-OVER {}", if *won { "WIN" } else { "LOSE" }),
+            Message::Error(message) => write!(f, "ERROR {}", message),
+        }
+    }
+}
+
+fn parse_num(word: &str) -> Result<usize> {
+    word.parse::<usize>()
+        .chain_err(|| format!("Expected a number, but got {}", word))
+}
+
+pub fn format_placement(placement: Placement) -> String {
+    let axis = match placement.incrementing_axis {
+        Axis::Col => "H",
+        Axis::Row => "V",
+    };
+
+    format!("{},{},{},{}", placement.col_num, placement.row_num, axis, placement.size)
+}
+
+fn parse_placement(word: &str) -> Result<Placement> {
+    let parts = word.split(',').collect::<Vec<_>>();
+    ensure!(parts.len() == 4, "Expected a ship like 0,3,H,5, but got {}", word);
+
+    let incrementing_axis = match parts[2] {
+        "H" => Axis::Col,
+        "V" => Axis::Row,
+        axis => bail!("Expected H or V for the direction of a ship, but got {}", axis),
+    };
+
+    Ok(Placement::new(parse_num(parts[0])?, parse_num(parts[1])?, incrementing_axis, parse_num(parts[3])?))
+}
+
+fn format_result(result: ShotResult) -> String {
+    match result {
+        ShotResult::Miss            => "MISS".to_string(),
+        ShotResult::Hit             => "HIT".to_string(),
+        ShotResult::Sunk(placement) => format!("SUNK {}", format_placement(placement)),
+    }
+}
+
+fn parse_placements(words: &[&str]) -> Result<Vec<Placement>> {
+    words.iter()
+        .map(|word| parse_placement(word))
+        .collect()
+}
+
+fn parse_result(words: &[&str]) -> Result<ShotResult> {
+    match words {
+        ["MISS"]       => Ok(ShotResult::Miss),
+        ["HIT"]        => Ok(ShotResult::Hit),
+        ["SUNK", ship] => Ok(ShotResult::Sunk(parse_placement(ship)?)),
+        _              => bail!("Unable to understand shot result: {}", words.join(" ")),
+    }
+}
+
+// One end of a TCP connection that sends and receives messages
+pub struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> Result<Self> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))
+            .chain_err(|| "Unable to set read timeout")?;
+        let writer = stream.try_clone()
+            .chain_err(|| "Unable to clone TCP stream")?;
+
+        Ok(Connection {
+            reader: BufReader::new(stream),
+            writer,
+        })
+    }
+
+    pub fn send(&mut self, message: &Message) -> Result<()> {
+        writeln!(self.writer, "{}", message)
+            .chain_err(|| format!("Unable to send message: {}", message))
+    }
+
+    pub fn receive(&mut self) -> Result<Message> {
+        let mut line = String::new();
+        let num_bytes = self.reader.read_line(&mut line)
+            .chain_err(|| "Unable to receive message")?;
+        ensure!(num_bytes > 0, "Connection closed");
+
+        Message::parse(&line)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn round_trip(line: &str, message: Message) -> Result<()> {
+        assert_eq!(Message::parse(line)?, message);
+        assert_eq!(message.to_string(), line);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_round_trips_messages() -> Result<()> {
+        round_trip("HELLO alice", Message::Hello { name: "alice".to_string() })?;
+        round_trip("WELCOME 1 10 8 5,4,3,3,2", Message::Welcome {
+            player_num: 1,
+            layout: Layout { num_cols: 10, num_rows: 8 },
+            fleet: standard_fleet(),
+            hidden_fleets: false,
+        })?;
+        round_trip("WELCOME 0 4 4 2,1 HIDDEN", Message::Welcome {
+            player_num: 0,
+            layout: Layout { num_cols: 4, num_rows: 4 },
+            fleet: vec![ExpectedShip { size: 2 }, ExpectedShip { size: 1 }],
+            hidden_fleets: true,
+        })?;
+        round_trip("FLEET 0,0,H,5 9,2,V,3", Message::Fleet(vec![
+            Placement::new(0, 0, Axis::Col, 5),
+            Placement::new(9, 2, Axis::Row, 3),
+        ]))?;
+        round_trip(&format!("COMMIT {}", "0f".repeat(32)), Message::Commit(Commitment([0x0f; 32])))?;
+        round_trip("FLEET-OK", Message::FleetOk)?;
+        round_trip("YOUR-TURN", Message::YourTurn)?;
+        round_trip("FIRE 3 4", Message::Fire { col_num: 3, row_num: 4 })?;
+        round_trip("RESULT 3 4 MISS", Message::Result { col_num: 3, row_num: 4, result: ShotResult::Miss })?;
+        round_trip("INCOMING 3 4 HIT", Message::Incoming { col_num: 3, row_num: 4, result: ShotResult::Hit })?;
+        round_trip("RESULT 9 4 SUNK 9,2,V,3", Message::Result {
+            col_num: 9,
+            row_num: 4,
+            result: ShotResult::Sunk(Placement::new(9, 2, Axis::Row, 3)),
+        })?;
+        round_trip("SHOT 3 4", Message::Shot { col_num: 3, row_num: 4 })?;
+        round_trip("ANSWER SUNK 9,2,V,3", Message::Answer(ShotResult::Sunk(Placement::new(9, 2, Axis::Row, 3))))?;
+        round_trip("REVEAL-FLEET", Message::RevealFleet)?;
+        round_trip("REVEAL 1a2b 0,0,H,5 9,2,V,3", Message::Reveal {
+            salt: "1a2b".to_string(),
+            placements: vec![
+                Placement::new(0, 0, Axis::Col, 5),
+                Placement::new(9, 2, Axis::Row, 3),
+            ],
+        })?;
+        round_trip("GAME-OVER WIN", Message::GameOver { won: true })?;
+        round_trip("GAME-OVER LOSE", Message::GameOver { won: false })?;
+        round_trip("ERROR That square was already shot at", Message::Error("That square was already shot at".to_string()))?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_bad_messages() {
+        assert!(Message::parse("").is_err());
+        assert!(Message::parse("FIRE 3").is_err());
+        assert!(Message::parse("FIRE three 4").is_err());
+        assert!(Message::parse("FLEET 0,0,D,5").is_err());
+        assert!(Message::parse("LAUNCH-MISSILES").is_err());
+        assert!(Message::parse("WELCOME 0 4 4 2,1 VISIBLE").is_err());
+        assert!(Message::parse("COMMIT 1234").is_err());
+    }
+}
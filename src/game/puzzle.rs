@@ -0,0 +1,264 @@
+/////////////////////////////////////////////////////////////////////
+//
+// Turning a real game into a solitaire puzzle.
+//
+// A hidden fleet is already most of a puzzle: the row and col counts and
+// the list of ships come straight from it. A few squares are given away,
+// such as the ones the opponent hit during the game. If that doesn't pin
+// down the fleet, more ship squares are given away until the puzzle has
+// exactly one solution.
+
+use std::collections::BTreeMap;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::board::*;
+use crate::solve::*;
+
+use super::*;
+
+// Write the puzzle in the usual text format. Only the squares in `reveals`
+// are shown, and the counts are for the ship squares that remain hidden.
+//
+// Errors if a count is more than 9. The format only has room for one digit
+// per row/col.
+pub fn puzzle_text(layout: Layout, placements: &[Placement], reveals: &[(usize, usize)]) -> Result<Vec<String>> {
+    let square_at = |col_num: usize, row_num: usize| -> Square {
+        let placement = placements.iter()
+            .find(|placement| placement.covers(col_num, row_num));
+
+        match placement {
+            None => Square::Water,
+            Some(placement) => {
+                let ship = placement.to_ship(&layout);
+                let square_idx = (col_num - placement.col_num) + (row_num - placement.row_num);
+                Square::ShipSquare(ship.expected_square_for_idx(square_idx))
+            },
+        }
+    };
+    let is_hidden_ship = |col_num: usize, row_num: usize|
+        square_at(col_num, row_num).is_ship() && !reveals.contains(&(col_num, row_num));
+
+    let mut sizes = BTreeMap::new();
+    for placement in placements.iter() {
+        *sizes.entry(placement.size).or_insert(0) += 1;
+    }
+    let ships = sizes.iter()
+        .rev()
+        .map(|(&size, count)| format!("{} x {}", ExpectedShip { size }, count))
+        .collect::<Vec<_>>();
+
+    let mut lines = vec![format!("ships: {}.", ships.join(", "))];
+
+    let col_counts = (0 .. layout.num_cols)
+        .map(|col_num| {
+            let count = (0 .. layout.num_rows).filter(|&row_num| is_hidden_ship(col_num, row_num)).count();
+            ensure!(count <= 9, "Col {} has {} hidden ship squares, which is too many to write down", col_num, count);
+            Ok(count.to_string())
+        })
+        .collect::<Result<String>>()?;
+    lines.push(format!("  {}", col_counts));
+
+    for row_num in 0 .. layout.num_rows {
+        let row_count = (0 .. layout.num_cols)
+            .filter(|&col_num| is_hidden_ship(col_num, row_num))
+            .count();
+        ensure!(row_count <= 9, "Row {} has {} hidden ship squares, which is too many to write down", row_num, row_count);
+        let squares = (0 .. layout.num_cols)
+            .map(|col_num| {
+                if reveals.contains(&(col_num, row_num)) {
+                    square_at(col_num, row_num)
+                }
+                else {
+                    Square::Unknown
+                }
+            })
+            .map(|square| square.to_string())
+            .collect::<String>();
+
+        lines.push(format!("{}|{}", row_count, squares));
+    }
+
+    Ok(lines)
+}
+
+// Make a puzzle with a unique solution, starting from the given reveals and
+// giving away random ship squares until the solver finds only one solution.
+// Returns the puzzle's text, which Board::new can read.
+pub fn generate_puzzle(layout: Layout, placements: &[Placement], reveals: &[(usize, usize)],
+    rng: &mut impl Rng) -> Result<Vec<String>> {
+
+    let mut reveals = reveals.to_vec();
+
+    loop {
+        let lines = puzzle_text(layout, placements, &reveals)?;
+        let board = Board::new(&lines.iter().map(String::as_str).collect::<Vec<_>>())?;
+
+        if count_solutions(&board, 2)? == 1 {
+            return Ok(lines);
+        }
+
+        let hidden_ship_squares = layout.all_coordinates()
+            .map(|coord| (coord.col_num, coord.row_num))
+            .filter(|&(col_num, row_num)| !reveals.contains(&(col_num, row_num))
+                && placements.iter().any(|placement| placement.covers(col_num, row_num)))
+            .collect::<Vec<_>>();
+
+        // With the whole fleet showing, there's nothing left to be ambiguous
+        let &reveal = hidden_ship_squares.choose(rng)
+            .ok_or("Unable to make a puzzle with a unique solution")?;
+        reveals.push(reveal);
+    }
+}
+
+// The squares of a player's fleet that their opponent hit
+pub fn hits_against(ocean: &OceanGrid) -> Vec<(usize, usize)> {
+    ocean.layout.all_coordinates()
+        .map(|coord| (coord.col_num, coord.row_num))
+        .filter(|&(col_num, row_num)| ocean.shot_at[row_num][col_num]
+            && ocean.placements.iter().any(|placement| placement.covers(col_num, row_num)))
+        .collect()
+}
+
+// Make a puzzle from a game's winner's fleet, giving away the squares the
+// loser hit
+pub fn puzzle_from_game(game: &Game, rng: &mut impl Rng) -> Result<Vec<String>> {
+    let winner = game.winner().ok_or("The game isn't over yet")?;
+    let ocean = &game.players[winner].ocean;
+
+    generate_puzzle(ocean.layout, &ocean.placements, &hits_against(ocean), rng)
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    //  0123
+    // 0<>
+    // 1
+    // 2   •
+    fn placements() -> Vec<Placement> {
+        vec![
+            Placement::new(0, 0, Axis::Col, 2),
+            Placement::new(3, 2, Axis::Row, 1),
+        ]
+    }
+
+    fn layout() -> Layout {
+        Layout { num_rows: 3, num_cols: 4 }
+    }
+
+    fn to_board(lines: &[String]) -> Result<Board> {
+        Board::new(&lines.iter().map(String::as_str).collect::<Vec<_>>())
+    }
+
+    // Do the ship squares on the board match the fleet?
+    fn matches_fleet(board: &Board, placements: &[Placement]) -> bool {
+        board.layout.all_coordinates()
+            .all(|coord| board[coord].is_ship()
+                == placements.iter().any(|placement| placement.covers(coord.col_num, coord.row_num)))
+    }
+
+    #[test]
+    fn it_writes_puzzle_text() -> Result<()> {
+        assert_eq!(puzzle_text(layout(), &placements(), &[(1, 0), (2, 2)])?, vec![
+            "ships: 2sq x 1, 1sq x 1.",
+            "  1001",
+            "1| >  ",
+            "0|    ",
+            "1|  ~ ",
+        ]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_reads_back_the_text_it_writes() -> Result<()> {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for _ in 0 .. 10 {
+            let placements = random_placements(&standard_layout(), &standard_fleet(), &mut rng)?;
+            let reveals = standard_layout().all_coordinates()
+                .map(|coord| (coord.col_num, coord.row_num))
+                .filter(|&(col_num, row_num)| (col_num + row_num) % 3 == 0)
+                .collect::<Vec<_>>();
+            let text = puzzle_text(standard_layout(), &placements, &reveals)?;
+
+            assert_eq!(to_board(&text)?.to_strings(), text);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_counts_of_more_than_one_digit() {
+        // Two 5 square ships in one row of 11
+        let layout = Layout { num_rows: 1, num_cols: 11 };
+        let placements = [Placement::new(0, 0, Axis::Col, 5), Placement::new(6, 0, Axis::Col, 5)];
+
+        assert!(puzzle_text(layout, &placements, &[]).is_err());
+        assert!(puzzle_text(layout, &placements, &[(0, 0)]).is_ok());
+    }
+
+    #[test]
+    fn it_generates_unique_puzzles() -> Result<()> {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for _ in 0 .. 10 {
+            let placements = random_placements(&standard_layout(), &standard_fleet(), &mut rng)?;
+            let mut board = to_board(&generate_puzzle(standard_layout(), &placements, &[], &mut rng)?)?;
+
+            assert_eq!(count_solutions(&board, 2)?, 1);
+            assert!(solve_by_search(&mut board)?);
+            assert!(matches_fleet(&board, &placements));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_keeps_requested_reveals() -> Result<()> {
+        let mut rng = StdRng::seed_from_u64(1);
+        let board = to_board(&generate_puzzle(layout(), &placements(), &[(0, 0), (2, 2)], &mut rng)?)?;
+
+        assert_eq!(board[board.layout.coord(0, 0)], Square::ShipSquare(ShipSquare::LeftEnd));
+        assert_eq!(board[board.layout.coord(2, 2)], Square::Water);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_needs_a_finished_game() -> Result<()> {
+        let mut rng = StdRng::seed_from_u64(1);
+        let game = Game::new(layout(), &[ExpectedShip { size: 2 }, ExpectedShip { size: 1 }], [placements(), placements()])?;
+
+        assert!(puzzle_from_game(&game, &mut rng).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_makes_puzzles_from_games() -> Result<()> {
+        let mut rng = StdRng::seed_from_u64(2);
+        let game = play_ai_game([Difficulty::Density, Difficulty::Density], standard_layout(), &standard_fleet(), &mut rng)?;
+
+        let winner = game.winner().unwrap();
+        let hits = hits_against(game.ocean(winner));
+        let mut board = to_board(&puzzle_from_game(&game, &mut rng)?)?;
+
+        // The loser's hits are given away
+        for &(col_num, row_num) in hits.iter() {
+            assert!(board[board.layout.coord(col_num, row_num)].is_ship());
+        }
+
+        assert_eq!(count_solutions(&board, 2)?, 1);
+        assert!(solve_by_search(&mut board)?);
+        assert!(matches_fleet(&board, &game.ocean(winner).placements));
+
+        Ok(())
+    }
+}
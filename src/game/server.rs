@@ -0,0 +1,516 @@
+/////////////////////////////////////////////////////////////////////
+//
+// Host a match between two players over TCP, and referee it.
+//
+// The server holds the real Game. Players only see what the protocol tells
+// them, so they can't peek at each other's fleets.
+
+use std::net::TcpListener;
+
+use super::*;
+
+// Stop listening to a player who keeps sending messages we can't use
+const MAX_ERRORS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchReport {
+    pub names: [String; 2],
+    pub winner: usize,
+    pub shots: [usize; 2],
+
+    // In a hidden-fleet match, which players' answers didn't match the fleet they revealed
+    pub cheated: [bool; 2],
+}
+
+// Wait for two players to connect, then play one match between them.
+// The first player to connect goes first.
+pub fn serve_match(listener: &TcpListener, layout: Layout, fleet: &[ExpectedShip]) -> Result<MatchReport> {
+    let (mut connections, names) = accept_players(listener, layout, fleet, false)?;
+
+    let placements = [
+        receive_fleet(&mut connections[0], layout, fleet)
+            .chain_err(|| format!("{} didn't provide a fleet", names[0]))?,
+        receive_fleet(&mut connections[1], layout, fleet)
+            .chain_err(|| format!("{} didn't provide a fleet", names[1]))?,
+    ];
+
+    let mut game = Game::new(layout, fleet, placements)?;
+    let mut shots = [0, 0];
+
+    while !game.is_over() {
+        let shooter = game.current_player();
+        let opponent = 1 - shooter;
+
+        connections[shooter].send(&Message::YourTurn)?;
+        let (col_num, row_num, result) = receive_shot(&mut connections[shooter], &mut game)?;
+        shots[shooter] += 1;
+
+        connections[shooter].send(&Message::Result { col_num, row_num, result })?;
+        connections[opponent].send(&Message::Incoming { col_num, row_num, result })?;
+    }
+
+    let winner = game.winner().unwrap();
+    for (player_num, connection) in connections.iter_mut().enumerate() {
+        connection.send(&Message::GameOver { won: player_num == winner })?;
+    }
+
+    Ok(MatchReport {
+        names,
+        winner,
+        shots,
+        cheated: [false, false],
+    })
+}
+
+// Like serve_match, but the server never sees the fleets until the end.
+// Players commit to their fleets and answer shots themselves, then reveal
+// their fleets so their answers can be checked. A player caught cheating
+// loses, unless both were.
+pub fn serve_hidden_match(listener: &TcpListener, layout: Layout, fleet: &[ExpectedShip]) -> Result<MatchReport> {
+    let (mut connections, names) = accept_players(listener, layout, fleet, true)?;
+
+    let commitments = [
+        receive_commitment(&mut connections[0])
+            .chain_err(|| format!("{} didn't commit to a fleet", names[0]))?,
+        receive_commitment(&mut connections[1])
+            .chain_err(|| format!("{} didn't commit to a fleet", names[1]))?,
+    ];
+
+    // The shots at each player's fleet, and how the player answered them
+    let mut shots_at = [Vec::new(), Vec::new()];
+    let mut num_sunk = [0, 0];
+    let mut shooter = 0;
+
+    let mut winner = loop {
+        let opponent = 1 - shooter;
+
+        connections[shooter].send(&Message::YourTurn)?;
+        let (col_num, row_num) = receive_hidden_shot(&mut connections[shooter], layout, &shots_at[opponent])?;
+
+        connections[opponent].send(&Message::Shot { col_num, row_num })?;
+        let result = receive_answer(&mut connections[opponent], col_num, row_num)
+            .chain_err(|| format!("{} didn't answer a shot", names[opponent]))?;
+        shots_at[opponent].push(ShotRecord { col_num, row_num, result });
+
+        connections[shooter].send(&Message::Result { col_num, row_num, result })?;
+
+        if let ShotResult::Sunk(_) = result {
+            num_sunk[opponent] += 1;
+        }
+
+        // If every square has been shot at and the fleet still isn't sunk, the
+        // opponent must have lied, and the reveal will show it
+        if num_sunk[opponent] == fleet.len() || shots_at[opponent].len() == layout.num_rows * layout.num_cols {
+            break shooter;
+        }
+
+        shooter = opponent;
+    };
+
+    let mut cheated = [false, false];
+    for player_num in 0 .. 2 {
+        connections[player_num].send(&Message::RevealFleet)?;
+
+        cheated[player_num] = match connections[player_num].receive() {
+            Ok(Message::Reveal { salt, placements }) =>
+                verify_fleet(commitments[player_num], &salt, &placements, layout, fleet, &shots_at[player_num]).is_err(),
+            _ => true,
+        };
+    }
+
+    if cheated[winner] && !cheated[1 - winner] {
+        winner = 1 - winner;
+    }
+
+    for (player_num, connection) in connections.iter_mut().enumerate() {
+        connection.send(&Message::GameOver { won: player_num == winner })?;
+    }
+
+    Ok(MatchReport {
+        names,
+        winner,
+        shots: [shots_at[1].len(), shots_at[0].len()],
+        cheated,
+    })
+}
+
+// Wait for both players to say hello, and welcome them
+fn accept_players(listener: &TcpListener, layout: Layout, fleet: &[ExpectedShip], hidden_fleets: bool)
+    -> Result<([Connection; 2], [String; 2])> {
+
+    let connect = |player_num: usize| -> Result<(Connection, String)> {
+        let (stream, _) = listener.accept()
+            .chain_err(|| "Unable to accept connection")?;
+        let mut connection = Connection::new(stream)?;
+
+        let name = match connection.receive()? {
+            Message::Hello { name } => name,
+            message => {
+                connection.send(&Message::Error("Expected HELLO".to_string()))?;
+                bail!("Player {} sent {} instead of HELLO", player_num, message);
+            },
+        };
+
+        connection.send(&Message::Welcome { player_num, layout, fleet: fleet.to_vec(), hidden_fleets })?;
+
+        Ok((connection, name))
+    };
+
+    let (connection_0, name_0) = connect(0)?;
+    let (connection_1, name_1) = connect(1)?;
+
+    Ok(([connection_0, connection_1], [name_0, name_1]))
+}
+
+// Keep asking for a fleet until the player sends a valid one
+fn receive_fleet(connection: &mut Connection, layout: Layout, fleet: &[ExpectedShip]) -> Result<Vec<Placement>> {
+    for _ in 0 .. MAX_ERRORS {
+        let error = match connection.receive() {
+            Ok(Message::Fleet(placements)) => match validate_fleet(&layout, fleet, &placements) {
+                Ok(()) => {
+                    connection.send(&Message::FleetOk)?;
+                    return Ok(placements);
+                },
+                Err(error) => error.to_string(),
+            },
+            Ok(message) => format!("Expected FLEET, but got {}", message),
+            Err(error)  => error.to_string(),
+        };
+
+        connection.send(&Message::Error(error))?;
+    }
+
+    bail!("Too many invalid messages")
+}
+
+// Keep asking for a shot until the player sends a valid one
+fn receive_shot(connection: &mut Connection, game: &mut Game) -> Result<(usize, usize, ShotResult)> {
+    for _ in 0 .. MAX_ERRORS {
+        let error = match connection.receive() {
+            Ok(Message::Fire { col_num, row_num }) => match game.fire(col_num, row_num) {
+                Ok(result) => return Ok((col_num, row_num, result)),
+                Err(error) => error.to_string(),
+            },
+            Ok(message) => format!("Expected FIRE, but got {}", message),
+            Err(error)  => error.to_string(),
+        };
+
+        connection.send(&Message::Error(error))?;
+    }
+
+    bail!("Too many invalid messages")
+}
+
+fn receive_commitment(connection: &mut Connection) -> Result<Commitment> {
+    for _ in 0 .. MAX_ERRORS {
+        let error = match connection.receive() {
+            Ok(Message::Commit(commitment)) => {
+                connection.send(&Message::FleetOk)?;
+                return Ok(commitment);
+            },
+            Ok(message) => format!("Expected COMMIT, but got {}", message),
+            Err(error)  => error.to_string(),
+        };
+
+        connection.send(&Message::Error(error))?;
+    }
+
+    bail!("Too many invalid messages")
+}
+
+// Like receive_shot, but without a Game to check the shot against
+fn receive_hidden_shot(connection: &mut Connection, layout: Layout, shots_at: &[ShotRecord]) -> Result<(usize, usize)> {
+    for _ in 0 .. MAX_ERRORS {
+        let error = match connection.receive() {
+            Ok(Message::Fire { col_num, row_num }) => {
+                if col_num >= layout.num_cols || row_num >= layout.num_rows {
+                    format!("Shot at row {}, col {} is off the board", row_num, col_num)
+                }
+                else if shots_at.iter().any(|shot| shot.col_num == col_num && shot.row_num == row_num) {
+                    format!("Row {}, col {} has already been shot at", row_num, col_num)
+                }
+                else {
+                    return Ok((col_num, row_num));
+                }
+            },
+            Ok(message) => format!("Expected FIRE, but got {}", message),
+            Err(error)  => error.to_string(),
+        };
+
+        connection.send(&Message::Error(error))?;
+    }
+
+    bail!("Too many invalid messages")
+}
+
+// Keep asking until the player answers a shot at their fleet. Only obvious
+// nonsense is rejected now; lies are caught when the fleet is revealed.
+fn receive_answer(connection: &mut Connection, col_num: usize, row_num: usize) -> Result<ShotResult> {
+    for _ in 0 .. MAX_ERRORS {
+        let error = match connection.receive() {
+            Ok(Message::Answer(ShotResult::Sunk(placement))) if !placement.covers(col_num, row_num) =>
+                "The sunk ship doesn't cover the square that was shot at".to_string(),
+            Ok(Message::Answer(result)) => return Ok(result),
+            Ok(message) => format!("Expected ANSWER, but got {}", message),
+            Err(error)  => error.to_string(),
+        };
+
+        connection.send(&Message::Error(error))?;
+    }
+
+    bail!("Too many invalid messages")
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::TcpStream;
+    use std::thread;
+
+    use super::*;
+
+    fn small_fleet() -> Vec<ExpectedShip> {
+        vec![ExpectedShip { size: 2 }, ExpectedShip { size: 1 }]
+    }
+
+    fn small_layout() -> Layout {
+        Layout { num_rows: 4, num_cols: 4 }
+    }
+
+    fn listen() -> Result<(TcpListener, String)> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .chain_err(|| "Unable to listen")?;
+        let address = listener.local_addr()
+            .chain_err(|| "Unable to get address")?
+            .to_string();
+
+        Ok((listener, address))
+    }
+
+    fn connect(address: &str) -> Result<Connection> {
+        let stream = TcpStream::connect(address)
+            .chain_err(|| "Unable to connect")?;
+
+        Connection::new(stream)
+    }
+
+    // Fire, and check what both players hear about it. A result of None means
+    // the shot should be rejected.
+    fn fire(players: &mut [Connection], shooter: usize, col_num: usize, row_num: usize,
+        result: Option<ShotResult>) -> Result<()> {
+
+        players[shooter].send(&Message::Fire { col_num, row_num })?;
+
+        match result {
+            None => assert!(matches!(players[shooter].receive()?, Message::Error(_))),
+            Some(result) => {
+                assert_eq!(players[shooter].receive()?, Message::Result { col_num, row_num, result });
+                assert_eq!(players[1 - shooter].receive()?, Message::Incoming { col_num, row_num, result });
+            },
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_plays_a_match_between_ai_clients() -> Result<()> {
+        let (listener, address) = listen()?;
+        let server = thread::spawn(move || serve_match(&listener, standard_layout(), &standard_fleet()));
+
+        let address_0 = address.clone();
+        let client_0 = thread::spawn(move || play_as_ai(&address_0, "density", Difficulty::Density, 1));
+        let client_1 = thread::spawn(move || play_as_ai(&address, "random", Difficulty::Random, 2));
+
+        let report = server.join().unwrap()?;
+        let won_0 = client_0.join().unwrap()?;
+        let won_1 = client_1.join().unwrap()?;
+
+        // Either client could have connected first
+        let mut names = report.names.clone();
+        names.sort();
+        assert_eq!(names, ["density".to_string(), "random".to_string()]);
+
+        let winner = report.names[report.winner].as_str();
+        assert_eq!(won_0, winner == "density");
+        assert_eq!(won_1, winner == "random");
+
+        // The standard fleet has 17 squares
+        assert!(report.shots[report.winner] >= 17);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_referees_fleets_and_shots() -> Result<()> {
+        let (listener, address) = listen()?;
+        let server = thread::spawn(move || serve_match(&listener, small_layout(), &small_fleet()));
+
+        let mut players = Vec::new();
+        for (player_num, name) in ["alice", "bob"].iter().enumerate() {
+            let mut player = connect(&address)?;
+            player.send(&Message::Hello { name: name.to_string() })?;
+            assert_eq!(player.receive()?, Message::Welcome {
+                player_num,
+                layout: small_layout(),
+                fleet: small_fleet(),
+                hidden_fleets: false,
+            });
+            players.push(player);
+        }
+
+        //  0123
+        // 0<>
+        // 1
+        // 2   •
+        let fleet = vec![
+            Placement::new(0, 0, Axis::Col, 2),
+            Placement::new(3, 2, Axis::Row, 1),
+        ];
+        let touching = vec![
+            Placement::new(0, 0, Axis::Col, 2),
+            Placement::new(0, 1, Axis::Row, 1),
+        ];
+
+        players[0].send(&Message::Fleet(touching))?;
+        assert!(matches!(players[0].receive()?, Message::Error(_)));
+        players[0].send(&Message::Fleet(fleet.clone()))?;
+        assert_eq!(players[0].receive()?, Message::FleetOk);
+        players[1].send(&Message::Fleet(fleet))?;
+        assert_eq!(players[1].receive()?, Message::FleetOk);
+
+        assert_eq!(players[0].receive()?, Message::YourTurn);
+        fire(&mut players, 0, 9, 9, None)?; // off the board
+        fire(&mut players, 0, 0, 0, Some(ShotResult::Hit))?;
+
+        assert_eq!(players[1].receive()?, Message::YourTurn);
+        fire(&mut players, 1, 3, 3, Some(ShotResult::Miss))?;
+
+        assert_eq!(players[0].receive()?, Message::YourTurn);
+        fire(&mut players, 0, 0, 0, None)?; // already shot there
+        fire(&mut players, 0, 1, 0, Some(ShotResult::Sunk(Placement::new(0, 0, Axis::Col, 2))))?;
+
+        assert_eq!(players[1].receive()?, Message::YourTurn);
+        players[1].send(&Message::YourTurn)?;
+        assert!(matches!(players[1].receive()?, Message::Error(_)));
+        fire(&mut players, 1, 2, 3, Some(ShotResult::Miss))?;
+
+        assert_eq!(players[0].receive()?, Message::YourTurn);
+        fire(&mut players, 0, 3, 2, Some(ShotResult::Sunk(Placement::new(3, 2, Axis::Row, 1))))?;
+
+        assert_eq!(players[0].receive()?, Message::GameOver { won: true });
+        assert_eq!(players[1].receive()?, Message::GameOver { won: false });
+
+        let report = server.join().unwrap()?;
+        assert_eq!(report, MatchReport {
+            names: ["alice".to_string(), "bob".to_string()],
+            winner: 0,
+            shots: [3, 2],
+            cheated: [false, false],
+        });
+
+        Ok(())
+    }
+
+    // In a hidden-fleet match, the shooter fires and the other player answers
+    fn fire_hidden(players: &mut [Connection], shooter: usize, col_num: usize, row_num: usize,
+        answer: ShotResult) -> Result<()> {
+
+        assert_eq!(players[shooter].receive()?, Message::YourTurn);
+        players[shooter].send(&Message::Fire { col_num, row_num })?;
+
+        assert_eq!(players[1 - shooter].receive()?, Message::Shot { col_num, row_num });
+        players[1 - shooter].send(&Message::Answer(answer))?;
+        assert_eq!(players[shooter].receive()?, Message::Result { col_num, row_num, result: answer });
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_plays_a_hidden_match_between_ai_clients() -> Result<()> {
+        let (listener, address) = listen()?;
+        let server = thread::spawn(move || serve_hidden_match(&listener, standard_layout(), &standard_fleet()));
+
+        let address_0 = address.clone();
+        let client_0 = thread::spawn(move || play_as_ai(&address_0, "density", Difficulty::Density, 1));
+        let client_1 = thread::spawn(move || play_as_ai(&address, "parity", Difficulty::Parity, 2));
+
+        let report = server.join().unwrap()?;
+        let won_0 = client_0.join().unwrap()?;
+        let won_1 = client_1.join().unwrap()?;
+
+        assert_eq!(report.cheated, [false, false]);
+        assert_ne!(won_0, won_1);
+        assert!(report.shots[report.winner] >= 17);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_catches_a_cheater_in_a_hidden_match() -> Result<()> {
+        let (listener, address) = listen()?;
+        let server = thread::spawn(move || serve_hidden_match(&listener, small_layout(), &small_fleet()));
+
+        //  0123
+        // 0<>
+        // 1
+        // 2   •
+        let fleet = vec![
+            Placement::new(0, 0, Axis::Col, 2),
+            Placement::new(3, 2, Axis::Row, 1),
+        ];
+
+        let mut players = Vec::new();
+        for name in ["alice", "bob"].iter() {
+            let mut player = connect(&address)?;
+            player.send(&Message::Hello { name: name.to_string() })?;
+            assert!(matches!(player.receive()?, Message::Welcome { hidden_fleets: true, .. }));
+            players.push(player);
+        }
+
+        for (player, name) in players.iter_mut().zip(["alice", "bob"].iter()) {
+            player.send(&Message::Fleet(fleet.clone()))?;
+            assert!(matches!(player.receive()?, Message::Error(_)));
+            player.send(&Message::Commit(Commitment::new(&fleet, name)))?;
+            assert_eq!(player.receive()?, Message::FleetOk);
+        }
+
+        // Bob claims alice's first shot missed
+        fire_hidden(&mut players, 0, 0, 0, ShotResult::Miss)?;
+        fire_hidden(&mut players, 1, 0, 0, ShotResult::Hit)?;
+        fire_hidden(&mut players, 0, 3, 3, ShotResult::Miss)?;
+
+        // A sunk ship has to cover the square that was shot at
+        assert_eq!(players[1].receive()?, Message::YourTurn);
+        players[1].send(&Message::Fire { col_num: 1, row_num: 0 })?;
+        assert_eq!(players[0].receive()?, Message::Shot { col_num: 1, row_num: 0 });
+        players[0].send(&Message::Answer(ShotResult::Sunk(Placement::new(3, 2, Axis::Row, 1))))?;
+        assert!(matches!(players[0].receive()?, Message::Error(_)));
+        players[0].send(&Message::Answer(ShotResult::Sunk(Placement::new(0, 0, Axis::Col, 2))))?;
+        assert_eq!(players[1].receive()?, Message::Result {
+            col_num: 1,
+            row_num: 0,
+            result: ShotResult::Sunk(Placement::new(0, 0, Axis::Col, 2)),
+        });
+
+        fire_hidden(&mut players, 0, 2, 3, ShotResult::Miss)?;
+        fire_hidden(&mut players, 1, 3, 2, ShotResult::Sunk(Placement::new(3, 2, Axis::Row, 1)))?;
+
+        // Bob sank alice's fleet first, but his reveal shows he lied
+        for (player, salt) in players.iter_mut().zip(["alice", "bob"].iter()) {
+            assert_eq!(player.receive()?, Message::RevealFleet);
+            player.send(&Message::Reveal { salt: salt.to_string(), placements: fleet.clone() })?;
+        }
+
+        assert_eq!(players[0].receive()?, Message::GameOver { won: true });
+        assert_eq!(players[1].receive()?, Message::GameOver { won: false });
+
+        let report = server.join().unwrap()?;
+        assert_eq!(report, MatchReport {
+            names: ["alice".to_string(), "bob".to_string()],
+            winner: 0,
+            shots: [3, 3],
+            cheated: [false, true],
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,785 @@
+/////////////////////////////////////////////////////////////////////
+//
+// Tournaments between Battleship bots.
+//
+// A bot is anything that implements Strategy. The built-in AI is one. A
+// program written by somebody else is another: it's run once per game, and
+// talks over stdin/stdout using the same messages as the TCP protocol,
+// without the handshake and fleet commitment:
+//
+//   tournament -> bot   WELCOME <player_num> <num_cols> <num_rows> <ship sizes>
+//   tournament -> bot   YOUR-TURN
+//   bot -> tournament   FIRE <col> <row>
+//   tournament -> bot   RESULT <col> <row> MISS|HIT|SUNK <ship>
+//   ...
+//   tournament -> bot   GAME-OVER WIN|LOSE
+//
+// The tournament hides the fleets itself, at random from a seed. Game n of
+// every match uses the same fleets, so every bot gets the same fleets to
+// find. A bot that makes an illegal shot, or stops answering, forfeits the
+// game.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use super::*;
+
+pub trait Strategy {
+    fn name(&self) -> String;
+
+    fn start_game(&mut self, player_num: usize, layout: Layout, fleet: &[ExpectedShip]) -> Result<()>;
+
+    // Choose where to fire next, as (col_num, row_num)
+    fn choose_shot(&mut self, target: &TargetGrid) -> Result<(usize, usize)>;
+
+    fn shot_result(&mut self, _col_num: usize, _row_num: usize, _result: ShotResult) -> Result<()> {
+        Ok(())
+    }
+
+    fn end_game(&mut self, _won: bool) -> Result<()> {
+        Ok(())
+    }
+}
+
+/////////////////////////////////////////////////////////////////////
+//
+// Strategies
+
+pub struct AiStrategy {
+    difficulty: Difficulty,
+    ai: Ai,
+    fleet: Vec<ExpectedShip>,
+}
+
+impl AiStrategy {
+    pub fn new(difficulty: Difficulty, seed: u64) -> Self {
+        AiStrategy {
+            difficulty,
+            ai: Ai::new(difficulty, seed),
+            fleet: Vec::new(),
+        }
+    }
+}
+
+impl Strategy for AiStrategy {
+    fn name(&self) -> String {
+        format!("{:?}", self.difficulty).to_lowercase()
+    }
+
+    fn start_game(&mut self, _player_num: usize, _layout: Layout, fleet: &[ExpectedShip]) -> Result<()> {
+        self.fleet = fleet.to_vec();
+        Ok(())
+    }
+
+    fn choose_shot(&mut self, target: &TargetGrid) -> Result<(usize, usize)> {
+        self.ai.choose_shot(target, &self.fleet)
+    }
+}
+
+// How long an external bot gets to answer, or to exit after the game
+pub const MOVE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// No message is anywhere near this long
+const MAX_LINE_LEN: usize = 1024;
+
+// A bot in another program
+pub struct ExternalStrategy {
+    name: String,
+    program: String,
+    args: Vec<String>,
+    pub move_timeout: Duration,
+    process: Option<(Child, ChildStdin, Receiver<io::Result<String>>)>,
+}
+
+impl ExternalStrategy {
+    pub fn new(name: &str, program: &str, args: &[&str]) -> Self {
+        ExternalStrategy {
+            name: name.to_string(),
+            program: program.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            move_timeout: MOVE_TIMEOUT,
+            process: None,
+        }
+    }
+
+    fn send(&mut self, message: &Message) -> Result<()> {
+        let (_, stdin, _) = self.process.as_mut().ok_or("Bot isn't running")?;
+        writeln!(stdin, "{}", message)
+            .chain_err(|| format!("Unable to send message to {}", self.name))
+    }
+
+    fn receive(&mut self) -> Result<Message> {
+        let (_, _, lines) = self.process.as_mut().ok_or("Bot isn't running")?;
+
+        let line = match lines.recv_timeout(self.move_timeout) {
+            Ok(line) => line.chain_err(|| format!("Unable to receive message from {}", self.name))?,
+            Err(RecvTimeoutError::Disconnected) => bail!("{} exited", self.name),
+            Err(RecvTimeoutError::Timeout) => {
+                self.kill();
+                bail!("{} didn't answer within {:?}", self.name, self.move_timeout)
+            },
+        };
+
+        Message::parse(&line)
+    }
+
+    // Let the bot exit by itself, if it does so in time
+    fn stop(&mut self) {
+        if let Some((mut child, stdin, _)) = self.process.take() {
+            drop(stdin);
+
+            let deadline = Instant::now() + self.move_timeout;
+            while let Ok(None) = child.try_wait() {
+                if Instant::now() >= deadline {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    break;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+    }
+
+    fn kill(&mut self) {
+        if let Some((mut child, _, _)) = self.process.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+// Read the bot's output on another thread, so the tournament can stop
+// waiting for it. Stops at the end of the output, or at a line that's too long.
+fn read_lines(stdout: ChildStdout) -> Receiver<io::Result<String>> {
+    // Don't read ahead of the tournament, so a bot that floods its output just blocks
+    let (sender, receiver) = mpsc::sync_channel(0);
+
+    thread::spawn(move || {
+        let mut stdout = BufReader::new(stdout);
+        loop {
+            let mut line = String::new();
+            let line = match stdout.by_ref().take(MAX_LINE_LEN as u64).read_line(&mut line) {
+                Ok(0) => return,
+                Ok(_) if !line.ends_with('\n') && line.len() == MAX_LINE_LEN => Err(io::Error::new(
+                    io::ErrorKind::InvalidData, format!("Line longer than {} bytes", MAX_LINE_LEN))),
+                Ok(_)  => Ok(line),
+                Err(e) => Err(e),
+            };
+
+            let is_err = line.is_err();
+            if sender.send(line).is_err() || is_err {
+                return;
+            }
+        }
+    });
+
+    receiver
+}
+
+impl Strategy for ExternalStrategy {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn start_game(&mut self, player_num: usize, layout: Layout, fleet: &[ExpectedShip]) -> Result<()> {
+        self.stop();
+
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .chain_err(|| format!("Unable to run {}", self.program))?;
+        let stdin = child.stdin.take().unwrap();
+        let lines = read_lines(child.stdout.take().unwrap());
+        self.process = Some((child, stdin, lines));
+
+        self.send(&Message::Welcome { player_num, layout, fleet: fleet.to_vec(), hidden_fleets: false })
+    }
+
+    fn choose_shot(&mut self, _target: &TargetGrid) -> Result<(usize, usize)> {
+        self.send(&Message::YourTurn)?;
+
+        match self.receive()? {
+            Message::Fire { col_num, row_num } => Ok((col_num, row_num)),
+            message => bail!("Expected FIRE from {}, but got {}", self.name, message),
+        }
+    }
+
+    fn shot_result(&mut self, col_num: usize, row_num: usize, result: ShotResult) -> Result<()> {
+        self.send(&Message::Result { col_num, row_num, result })
+    }
+
+    fn end_game(&mut self, won: bool) -> Result<()> {
+        // The bot may have already quit, and that's fine
+        let _ = self.send(&Message::GameOver { won });
+        self.stop();
+
+        Ok(())
+    }
+}
+
+impl Drop for ExternalStrategy {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+// Be an external bot: play games over stdin/stdout until the input ends
+pub fn play_as_bot(difficulty: Difficulty, seed: u64, input: impl BufRead, mut output: impl Write) -> Result<()> {
+    let mut ai = Ai::new(difficulty, seed);
+    let mut game_state = None;
+
+    for line in input.lines() {
+        let line = line.chain_err(|| "Unable to read input")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match Message::parse(&line)? {
+            Message::Welcome { layout, fleet, .. } => {
+                game_state = Some((TargetGrid::new(layout), fleet));
+            },
+            Message::YourTurn => {
+                let (target, fleet) = game_state.as_ref().ok_or("Got YOUR-TURN before WELCOME")?;
+                let (col_num, row_num) = ai.choose_shot(target, fleet)?;
+                writeln!(output, "{}", Message::Fire { col_num, row_num })
+                    .chain_err(|| "Unable to write output")?;
+                output.flush().chain_err(|| "Unable to write output")?;
+            },
+            Message::Result { col_num, row_num, result } => {
+                let (target, _) = game_state.as_mut().ok_or("Got RESULT before WELCOME")?;
+                target.record(col_num, row_num, result)?;
+            },
+            Message::GameOver { .. } => game_state = None,
+            message => bail!("Unexpected message: {}", message),
+        }
+    }
+
+    Ok(())
+}
+
+/////////////////////////////////////////////////////////////////////
+//
+// Playing games
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Pairing {
+    // Everyone plays everyone once
+    RoundRobin,
+    // Each round, players with similar records play each other
+    Swiss { rounds: usize },
+}
+
+#[derive(Debug, Clone)]
+pub struct TournamentConfig {
+    pub layout: Layout,
+    pub fleet: Vec<ExpectedShip>,
+    pub pairing: Pairing,
+    // Each match is this many games. Players take turns going first.
+    pub games_per_match: usize,
+    pub seed: u64,
+}
+
+impl Default for TournamentConfig {
+    fn default() -> Self {
+        TournamentConfig {
+            layout: standard_layout(),
+            fleet: standard_fleet(),
+            pairing: Pairing::RoundRobin,
+            games_per_match: 10,
+            seed: 0,
+        }
+    }
+}
+
+// How one bot did over the whole tournament
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub games: usize,
+    pub wins: usize,
+
+    // Games this bot lost by forfeiting. They count as wins for the other bot.
+    pub forfeits: usize,
+
+    // How many shots each win took. Wins by forfeit aren't included.
+    pub shots_to_win: Vec<usize>,
+}
+
+impl Standing {
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 { 0.0 } else { self.wins as f64 / self.games as f64 }
+    }
+
+    // 95% Wilson score interval for the win rate
+    pub fn win_rate_interval(&self) -> (f64, f64) {
+        if self.games == 0 {
+            return (0.0, 1.0);
+        }
+
+        let z: f64 = 1.96;
+        let n = self.games as f64;
+        let p = self.win_rate();
+
+        let center = (p + z * z / (2.0 * n)) / (1.0 + z * z / n);
+        let margin = z * (p * (1.0 - p) / n + z * z / (4.0 * n * n)).sqrt() / (1.0 + z * z / n);
+
+        ((center - margin).max(0.0), (center + margin).min(1.0))
+    }
+
+    pub fn average_shots_to_win(&self) -> Option<f64> {
+        if self.shots_to_win.is_empty() {
+            return None;
+        }
+
+        let total = self.shots_to_win.iter().sum::<usize>();
+        Some(total as f64 / self.shots_to_win.len() as f64)
+    }
+
+    // 95% confidence interval for the average shots to win, using the normal approximation
+    pub fn average_shots_interval(&self) -> Option<(f64, f64)> {
+        let mean = self.average_shots_to_win()?;
+        let n = self.shots_to_win.len() as f64;
+        if self.shots_to_win.len() < 2 {
+            return Some((mean, mean));
+        }
+
+        let variance = self.shots_to_win.iter()
+            .map(|&shots| (shots as f64 - mean).powi(2))
+            .sum::<f64>() / (n - 1.0);
+        let margin = 1.96 * (variance / n).sqrt();
+
+        Some((mean - margin, mean + margin))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standings(pub Vec<Standing>);
+
+impl fmt::Display for Standings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:<16} {:>5} {:>5} {:>8} {:>17} {:>20}",
+            "bot", "games", "wins", "forfeits", "win rate (95% CI)", "shots to win (95% CI)")?;
+
+        for standing in self.0.iter() {
+            let (low, high) = standing.win_rate_interval();
+            let win_rate = format!("{:.0}% ({:.0}-{:.0}%)", standing.win_rate() * 100.0, low * 100.0, high * 100.0);
+
+            let shots = match (standing.average_shots_to_win(), standing.average_shots_interval()) {
+                (Some(mean), Some((low, high))) => format!("{:.1} ({:.1}-{:.1})", mean, low, high),
+                _ => "-".to_string(),
+            };
+
+            writeln!(f, "{:<16} {:>5} {:>5} {:>8} {:>17} {:>20}",
+                standing.name, standing.games, standing.wins, standing.forfeits, win_rate, shots)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Play every game in the tournament. Standings are sorted best first.
+pub fn run_tournament(bots: &mut [Box<dyn Strategy>], config: &TournamentConfig) -> Result<Standings> {
+    ensure!(bots.len() >= 2, "A tournament needs at least two bots");
+
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let fleets = (0 .. config.games_per_match)
+        .map(|_| Ok([
+            random_placements(&config.layout, &config.fleet, &mut rng)?,
+            random_placements(&config.layout, &config.fleet, &mut rng)?,
+        ]))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut standings = bots.iter()
+        .map(|bot| Standing { name: bot.name(), games: 0, wins: 0, forfeits: 0, shots_to_win: Vec::new() })
+        .collect::<Vec<_>>();
+
+    let rounds = match config.pairing {
+        Pairing::RoundRobin => vec![round_robin_pairs(bots.len())],
+        Pairing::Swiss { rounds } => vec![Vec::new(); rounds],
+    };
+
+    let mut played = HashSet::new();
+    for round in rounds {
+        let pairs = match config.pairing {
+            Pairing::RoundRobin => round,
+            Pairing::Swiss { .. } => swiss_pairs(&standings, &played),
+        };
+
+        for (a, b) in pairs {
+            played.insert((a.min(b), a.max(b)));
+
+            for (game_num, placements) in fleets.iter().enumerate() {
+                // Take turns going first
+                let players = if game_num % 2 == 0 { [a, b] } else { [b, a] };
+
+                let (winner, shots) = play_game(bots, players, config, placements.clone())?;
+                for &player in players.iter() {
+                    standings[player].games += 1;
+                }
+                standings[players[winner]].wins += 1;
+
+                match shots {
+                    Some(shots) => standings[players[winner]].shots_to_win.push(shots),
+                    None        => standings[players[1 - winner]].forfeits += 1,
+                }
+            }
+        }
+    }
+
+    standings.sort_by(|a, b| b.win_rate().partial_cmp(&a.win_rate()).unwrap());
+
+    Ok(Standings(standings))
+}
+
+fn round_robin_pairs(num_bots: usize) -> Vec<(usize, usize)> {
+    (0 .. num_bots)
+        .flat_map(|a| (a + 1 .. num_bots).map(move |b| (a, b)))
+        .collect()
+}
+
+// Pair bots with similar numbers of wins, avoiding rematches when possible.
+// With an odd number of bots, the bot with the fewest wins sits out.
+fn swiss_pairs(standings: &[Standing], played: &HashSet<(usize, usize)>) -> Vec<(usize, usize)> {
+    let mut unpaired = (0 .. standings.len()).collect::<Vec<_>>();
+    unpaired.sort_by_key(|&bot| std::cmp::Reverse(standings[bot].wins));
+
+    let mut pairs = Vec::new();
+    while unpaired.len() >= 2 {
+        let a = unpaired.remove(0);
+        let opponent_idx = unpaired.iter()
+            .position(|&b| !played.contains(&(a.min(b), a.max(b))))
+            .unwrap_or(0);
+        let b = unpaired.remove(opponent_idx);
+
+        pairs.push((a, b));
+    }
+
+    pairs
+}
+
+// Returns the winner (0 or 1, an index into players), and the number of shots
+// they fired. Or None instead of the shots, if the other player forfeited.
+fn play_game(bots: &mut [Box<dyn Strategy>], players: [usize; 2], config: &TournamentConfig,
+    placements: [Vec<Placement>; 2]) -> Result<(usize, Option<usize>)> {
+
+    let mut game = Game::new(config.layout, &config.fleet, placements)?;
+
+    // A bot that can't start the game, say because it quit right away, forfeits it
+    let no_show = (0 .. 2).find(|&player_num|
+        bots[players[player_num]].start_game(player_num, config.layout, &config.fleet).is_err());
+
+    let winner = match no_show {
+        Some(player_num) => (1 - player_num, None),
+        None             => play_turns(bots, players, &mut game),
+    };
+
+    for (player_num, &bot) in players.iter().enumerate() {
+        bots[bot].end_game(player_num == winner.0)?;
+    }
+
+    Ok(winner)
+}
+
+// Take turns until someone wins, or forfeits. Returns the winner, and how many
+// shots they took, or None if the other player forfeited.
+fn play_turns(bots: &mut [Box<dyn Strategy>], players: [usize; 2], game: &mut Game) -> (usize, Option<usize>) {
+    let mut shots = [0, 0];
+    loop {
+        let shooter = game.current_player();
+        let bot = &mut bots[players[shooter]];
+
+        let fired = bot.choose_shot(game.target(shooter))
+            .and_then(|(col_num, row_num)| {
+                let result = game.fire(col_num, row_num)?;
+                Ok((col_num, row_num, result))
+            });
+
+        // An illegal shot forfeits the game
+        let (col_num, row_num, result) = match fired {
+            Ok(fired) => fired,
+            Err(_)    => break (1 - shooter, None),
+        };
+
+        shots[shooter] += 1;
+        let told = bot.shot_result(col_num, row_num, result);
+
+        if let Some(winner) = game.winner() {
+            break (winner, Some(shots[winner]));
+        }
+
+        // So does a bot that can't be told how its shot went
+        if told.is_err() {
+            break (1 - shooter, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    fn small_config(pairing: Pairing) -> TournamentConfig {
+        TournamentConfig {
+            layout: Layout { num_rows: 6, num_cols: 6 },
+            fleet: vec![ExpectedShip { size: 3 }, ExpectedShip { size: 2 }, ExpectedShip { size: 1 }],
+            pairing,
+            games_per_match: 6,
+            seed: 1,
+        }
+    }
+
+    // A bot that fires at every square in order
+    const SWEEPER: &str = "
+        n=0
+        while read -r line; do
+            case \"$line\" in
+                WELCOME*)   set -- $line; cols=$3 ;;
+                YOUR-TURN)  echo \"FIRE $((n % cols)) $((n / cols))\"; n=$((n + 1)) ;;
+                GAME-OVER*) exit 0 ;;
+            esac
+        done";
+
+    // A bot that keeps firing at the same square
+    const BROKEN: &str = "while read -r line; do echo 'FIRE 0 0'; done";
+
+    // A bot that fires once, then quits
+    const QUITTER: &str = "read -r line; read -r line; echo 'FIRE 0 0'";
+
+    // A bot that can't even be started
+    struct NoShow;
+
+    impl Strategy for NoShow {
+        fn name(&self) -> String {
+            "no-show".to_string()
+        }
+
+        fn start_game(&mut self, _player_num: usize, _layout: Layout, _fleet: &[ExpectedShip]) -> Result<()> {
+            bail!("Broken pipe")
+        }
+
+        fn choose_shot(&mut self, _target: &TargetGrid) -> Result<(usize, usize)> {
+            bail!("Never started")
+        }
+    }
+
+    // Bots that never finish answering
+    const SLEEPER: &str = "exec sleep 60";
+    const FLOODER: &str = "exec cat /dev/zero";
+
+    #[test]
+    fn it_plays_round_robin() -> Result<()> {
+        let mut bots: Vec<Box<dyn Strategy>> = vec![
+            Box::new(AiStrategy::new(Difficulty::Density, 1)),
+            Box::new(AiStrategy::new(Difficulty::Random, 2)),
+            Box::new(ExternalStrategy::new("sweeper", "sh", &["-c", SWEEPER])),
+        ];
+
+        let Standings(standings) = run_tournament(&mut bots, &small_config(Pairing::RoundRobin))?;
+
+        // Each bot plays 2 matches of 6 games
+        assert!(standings.iter().all(|standing| standing.games == 12));
+        assert_eq!(standings.iter().map(|standing| standing.wins).sum::<usize>(), 18);
+        assert_eq!(standings[0].name, "density");
+
+        for standing in standings.iter() {
+            let (low, high) = standing.win_rate_interval();
+            assert!(low <= standing.win_rate() && standing.win_rate() <= high);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_forfeits_illegal_shots() -> Result<()> {
+        let mut bots: Vec<Box<dyn Strategy>> = vec![
+            Box::new(ExternalStrategy::new("broken", "sh", &["-c", BROKEN])),
+            Box::new(AiStrategy::new(Difficulty::Random, 2)),
+        ];
+
+        let Standings(standings) = run_tournament(&mut bots, &small_config(Pairing::RoundRobin))?;
+
+        assert_eq!(standings[0].name, "random");
+        assert_eq!(standings[0].wins, 6);
+
+        // Wins by forfeit don't say anything about how many shots it takes to win
+        assert!(standings[0].shots_to_win.is_empty());
+        assert_eq!(standings[1].forfeits, 6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_forfeits_bots_that_quit() -> Result<()> {
+        let mut bots: Vec<Box<dyn Strategy>> = vec![
+            Box::new(ExternalStrategy::new("quitter", "sh", &["-c", QUITTER])),
+            Box::new(AiStrategy::new(Difficulty::Random, 2)),
+        ];
+
+        let Standings(standings) = run_tournament(&mut bots, &small_config(Pairing::RoundRobin))?;
+
+        assert_eq!(standings[1].name, "quitter");
+        assert_eq!(standings[1].forfeits, 6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_forfeits_bots_that_cant_start() -> Result<()> {
+        let mut bots: Vec<Box<dyn Strategy>> = vec![
+            Box::new(NoShow),
+            Box::new(AiStrategy::new(Difficulty::Random, 2)),
+        ];
+
+        let Standings(standings) = run_tournament(&mut bots, &small_config(Pairing::RoundRobin))?;
+
+        assert_eq!(standings[1].name, "no-show");
+        assert_eq!(standings[1].forfeits, 6);
+        assert!(standings[0].shots_to_win.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_forfeits_bots_that_take_too_long() -> Result<()> {
+        for &(name, script) in [("sleeper", SLEEPER), ("flooder", FLOODER)].iter() {
+            let mut bot = ExternalStrategy::new(name, "sh", &["-c", script]);
+            bot.move_timeout = Duration::from_millis(100);
+
+            let mut bots: Vec<Box<dyn Strategy>> = vec![
+                Box::new(bot),
+                Box::new(AiStrategy::new(Difficulty::Random, 2)),
+            ];
+
+            let Standings(standings) = run_tournament(&mut bots, &small_config(Pairing::RoundRobin))?;
+
+            assert_eq!(standings[1].name, name);
+            assert_eq!(standings[1].forfeits, 6);
+        }
+
+        Ok(())
+    }
+
+    // Fires at every square in order, and remembers how each shot went
+    struct Sweeper {
+        num_cols: usize,
+        num_shots: usize,
+        games: Rc<RefCell<Vec<Vec<ShotResult>>>>,
+    }
+
+    impl Strategy for Sweeper {
+        fn name(&self) -> String {
+            "sweeper".to_string()
+        }
+
+        fn start_game(&mut self, _player_num: usize, layout: Layout, _fleet: &[ExpectedShip]) -> Result<()> {
+            self.num_cols = layout.num_cols;
+            self.num_shots = 0;
+            self.games.borrow_mut().push(Vec::new());
+            Ok(())
+        }
+
+        fn choose_shot(&mut self, _target: &TargetGrid) -> Result<(usize, usize)> {
+            self.num_shots += 1;
+            Ok(((self.num_shots - 1) % self.num_cols, (self.num_shots - 1) / self.num_cols))
+        }
+
+        fn shot_result(&mut self, _col_num: usize, _row_num: usize, result: ShotResult) -> Result<()> {
+            self.games.borrow_mut().last_mut().unwrap().push(result);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn it_hides_the_same_fleets_in_every_match() -> Result<()> {
+        let games = (0 .. 3).map(|_| Rc::new(RefCell::new(Vec::new()))).collect::<Vec<_>>();
+        let mut bots = games.iter()
+            .map(|games| Box::new(Sweeper { num_cols: 0, num_shots: 0, games: games.clone() }) as Box<dyn Strategy>)
+            .collect::<Vec<_>>();
+
+        run_tournament(&mut bots, &small_config(Pairing::RoundRobin))?;
+
+        // The first bot plays the second, then the third, from the same seat.
+        // Against the same fleets, it sees the same shots go the same way.
+        let games = games[0].borrow();
+        assert_eq!(games.len(), 12);
+        assert_eq!(games[.. 6], games[6 ..]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_plays_swiss_rounds() -> Result<()> {
+        let mut bots: Vec<Box<dyn Strategy>> = vec![
+            Box::new(AiStrategy::new(Difficulty::Density, 1)),
+            Box::new(AiStrategy::new(Difficulty::Parity, 2)),
+            Box::new(AiStrategy::new(Difficulty::Random, 3)),
+            Box::new(AiStrategy::new(Difficulty::Random, 4)),
+        ];
+
+        let Standings(standings) = run_tournament(&mut bots, &small_config(Pairing::Swiss { rounds: 2 }))?;
+
+        // Everyone plays one match per round
+        assert!(standings.iter().all(|standing| standing.games == 12));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_pairs_without_rematches() {
+        let standing = |wins| Standing { name: String::new(), games: 0, wins, forfeits: 0, shots_to_win: Vec::new() };
+        let standings = vec![standing(3), standing(2), standing(1), standing(0)];
+
+        assert_eq!(swiss_pairs(&standings, &HashSet::new()), vec![(0, 1), (2, 3)]);
+
+        let played = [(0, 1), (2, 3)].iter().cloned().collect();
+        assert_eq!(swiss_pairs(&standings, &played), vec![(0, 2), (1, 3)]);
+    }
+
+    #[test]
+    fn it_computes_confidence_intervals() {
+        let standing = Standing {
+            name: "bot".to_string(),
+            games: 100,
+            wins: 50,
+            forfeits: 0,
+            shots_to_win: vec![40, 50, 60],
+        };
+
+        let (low, high) = standing.win_rate_interval();
+        assert!((low - 0.404).abs() < 0.001, "{}", low);
+        assert!((high - 0.596).abs() < 0.001, "{}", high);
+
+        assert_eq!(standing.average_shots_to_win(), Some(50.0));
+        let (low, high) = standing.average_shots_interval().unwrap();
+        assert!((low - 38.684).abs() < 0.001, "{}", low);
+        assert!((high - 61.316).abs() < 0.001, "{}", high);
+    }
+
+    #[test]
+    fn it_plays_as_external_bot() -> Result<()> {
+        let input = "WELCOME 0 2 1 1\nYOUR-TURN\nRESULT 0 0 MISS\nYOUR-TURN\n";
+        let mut output = Vec::new();
+
+        play_as_bot(Difficulty::Density, 0, input.as_bytes(), &mut output)?;
+
+        let output = String::from_utf8(output).unwrap();
+        let shots = output.lines().collect::<Vec<_>>();
+        assert_eq!(shots.len(), 2);
+        assert_eq!(shots[1], "FIRE 1 0");
+
+        Ok(())
+    }
+}
@@ -1,11 +1,10 @@
 use std::fmt;
 
-use crate::error::*;
 use crate::neighbor::*;
 use crate::ship::*;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
-pub struct Coord<'a> {
+pub(crate) struct Coord<'a> {
     pub row_num: usize,
     pub col_num: usize,
 
@@ -14,16 +13,16 @@ pub struct Coord<'a> {
 
 impl<'a> Coord<'a> {
     // Return the row or col of this coord, whichever is specified by the axis
-    pub fn row_or_col(&self, axis: Axis) -> RowOrCol {
+    pub(crate) fn row_or_col(&self, axis: Axis) -> RowOrCol<'_> {
         let index = self.index_for_axis(axis);
         self.layout.row_or_col(axis, index)
     }
 
-    pub fn row(&self) -> RowOrCol {
+    pub fn row(&self) -> RowOrCol<'_> {
         self.row_or_col(Axis::Row)
     }
 
-    pub fn col(&self) -> RowOrCol {
+    pub fn col(&self) -> RowOrCol<'_> {
         self.row_or_col(Axis::Col)
     }
 
@@ -110,7 +109,7 @@ impl fmt::Display for Axis {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub struct RowOrCol<'b> {
+pub(crate) struct RowOrCol<'b> {
     pub axis: Axis,
     pub index: usize,
 
@@ -121,7 +120,7 @@ pub struct RowOrCol<'b> {
 // function signatures?
 impl<'b> RowOrCol<'b> {
     // Return all the coordinates along the specified row or col
-    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+    pub fn coords(&self) -> impl Iterator<Item = Coord<'_>> {
         // Count number of items in the minor axis
         let minor_axis_ubound = match self.axis {
             Axis::Row => self.layout.num_cols,
@@ -156,33 +155,31 @@ pub struct Layout {
 }
 
 impl Layout {
-    pub fn coord(&self, col_num: usize, row_num: usize) -> Coord {
+    pub(crate) fn coord(&self, col_num: usize, row_num: usize) -> Coord<'_> {
         Coord {
             row_num, 
             col_num,
-            layout: &self,
+            layout: self,
         }
     }
 
-    pub fn row_or_col(&self, axis: Axis, index: usize) -> RowOrCol {
+    pub(crate) fn row_or_col(&self, axis: Axis, index: usize) -> RowOrCol<'_> {
         RowOrCol {
             axis,
             index,
-            layout: &self,
+            layout: self,
         }
     }
 
-    #[allow(dead_code)] // currently, this is only used in tests
-    pub fn row(&self, index: usize) -> RowOrCol {
+    pub(crate) fn row(&self, index: usize) -> RowOrCol<'_> {
         self.row_or_col(Axis::Row, index)
     }
 
-    #[allow(dead_code)] // currently, this is only used in tests
-    pub fn col(&self, index: usize) -> RowOrCol {
+    pub(crate) fn col(&self, index: usize) -> RowOrCol<'_> {
         self.row_or_col(Axis::Col, index)
     }
 
-    pub fn all_coordinates<'a>(&'a self) -> impl Iterator<Item = Coord> + 'a {
+    pub(crate) fn all_coordinates<'a>(&'a self) -> impl Iterator<Item = Coord<'a>> + 'a {
         // Don't want to capture self in any of the closures we return.
         // TODO: Not sure that matters
         let num_rows = self.num_rows;
@@ -198,7 +195,7 @@ impl Layout {
     }    
 
     // Iterate over every row and column in the layout
-    pub fn rows_and_cols<'a>(&'a self) -> impl Iterator<Item = RowOrCol> + 'a {
+    pub(crate) fn rows_and_cols<'a>(&'a self) -> impl Iterator<Item = RowOrCol<'a>> + 'a {
         let rows = (0 .. self.num_rows)
             .map(move |row_num| self.row_or_col(Axis::Row, row_num) );
 
@@ -215,7 +212,7 @@ impl Layout {
     // It only returns values that will be in bounds
     //
     // TODO: This could become a method on ExpectedShip. But ExpectedShip doesn't have access to the layout
-    pub fn possible_heads_for_ship<'a>(&'a self, expected_ship: ExpectedShip) -> impl Iterator<Item = ShipHead<'a>> + 'a {
+    pub(crate) fn possible_heads_for_ship<'a>(&'a self, expected_ship: ExpectedShip) -> impl Iterator<Item = ShipHead<'a>> + 'a {
         // When placing size = 1, we don't increment the coordinate so axis doesn't matter. But if we
         // search by both axes, every coord will match twice. So only search by one axis, and we only match
         // every candidate coordinate once.
@@ -228,7 +225,7 @@ impl Layout {
 
         axes.iter()
             .cloned()
-            .map(move |incrementing_axis| {
+            .flat_map(move |incrementing_axis| {
                 // For each incrementing_axis, produce an iterator that generates
                 // possible origins along that axis.
                 // Iterator 1: (_, Row), (_, Row), (_, Row)
@@ -251,7 +248,6 @@ impl Layout {
                         }
                     })
             })
-            .flatten()
     }
 }
 
@@ -261,6 +257,7 @@ impl Layout {
 mod layout_tests {
     use std::collections::HashSet;
     use super::*;
+    use crate::error::*;
 
     #[test]
     fn it_returns_all_coordinates() -> Result<()> {
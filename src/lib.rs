@@ -0,0 +1,51 @@
+// `error_chain!` can recurse deeply
+#![recursion_limit = "1024"]
+
+// Solve Battleship solitaire puzzles, and play classic two-player Battleship.
+//
+// The public API is re-exported here:
+// - Building boards: Board::new, parse_board, and Board::new_from_data
+// - Reading boards: Board::layout and Board::square
+// - Formatting boards: Board::to_strings, in either TextStyle
+// - Solving: solve and its variations, with SolveConfig for limits and
+//   SolveObserver for progress. Observers see each rule's changes as
+//   SquareChanges. Each rule can also be run on its own, with apply_rule.
+// - Validation: validate_board
+// - SAT solvers: write_dimacs, and decode_sat_model for the solver's answer
+// - A C ABI, declared in ffi/communicator.h
+// - The two-player game lives in the `game` module
+//
+// Everything else is an implementation detail.
+
+#[macro_use]
+extern crate error_chain;
+
+mod board;
+mod cnf;
+mod error;
+mod ffi;
+mod layout;
+mod neighbor;
+mod parse;
+mod ship;
+mod solve;
+mod square;
+mod test_utils;
+mod validate;
+
+pub mod game;
+
+pub use crate::board::{Board, FleetSource, SquareChange};
+pub use crate::cnf::{decode_sat_model, write_dimacs};
+pub use crate::error::{Error, ErrorKind, Result, ResultExt};
+pub use crate::layout::{Axis, Layout};
+pub use crate::parse::{parse_board, ParseError};
+pub use crate::ship::{standard_puzzle_fleet, ExpectedShip};
+pub use crate::solve::{
+    solve, solve_by_search, solve_with_config, solve_with_probing, solve_with_search,
+    count_solutions, probe, apply_rule, rule_names, Deduction,
+    CancellationToken, ProgressCallback, SolveConfig, SolveOutcome, SolveResult,
+    observer_from_name, JsonLines, Quiet, SolveEvent, SolveObserver, Summary, VerboseText,
+};
+pub use crate::square::{ShipSquare, Square, TextStyle};
+pub use crate::validate::validate_board;
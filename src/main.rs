@@ -1,165 +1,227 @@
-// `error_chain!` can recurse deeply
-#![recursion_limit = "1024"]
-
 #[macro_use]
 extern crate error_chain;
 
-mod board;
-mod error;
-mod layout;
-mod neighbor;
-mod parse;
-mod ship;
-mod solve;
-mod square;
-mod test_utils;
+use std::fs;
+use std::io;
+use std::time::{Duration, Instant};
+
+use rand::SeedableRng;
 
-use crate::board::*;
-use crate::solve::*;	
-use crate::error::*;
+use communicator::*;
+use communicator::game::*;
+
+type Solver = Box<dyn Fn(&mut Board, &mut dyn SolveObserver) -> Result<bool>>;
 
 fn run() -> Result<()> {
-    let _puzzle1 = vec![
-        "  112121",
-        "2|      ",
-        "0|      ",
-        "4| >    ",
-        "0|      ",
-        "2|     •",
-        "0|      ",
-    ];
-
-    let _puzzle2 = vec![
-        "  1304131",
-        "0|       ",
-        "5|       ",
-        "0|       ",
-        "1| >     ",
-        "2|       ",
-        "2|       ",
-        "3|       ",
-    ];
-
-    let _puzzle3 = vec![
-        "ships: 5sq x 1, 4sq x 1, 3sq x 2, ",
-        "       2sq x 3, 1sq x 4.",
-        "  3014161320",
-        "0|         •",
-        "4|          ",
-        "1| <        ",
-        "0|          ",
-        "3|          ",
-        "2|        v ",
-        "2|v         ",
-        "4|          ",
-        "2|          ",
-        "3|   ~      ",
-    ];
-
-    let _puzzle4 = vec![
-        // May not be deterministically solvable
-        "ships: 5sq x 1, 4sq x 2, 3sq x 3, ",
-        "       2sq x 4, 1sq x 4.",
-        "  021343411141121",
-        "0|       •       ",
-        "3|               ",
-        "1|          ☐    ",
-        "0|               ",
-        "1|               ",
-        "1|               ",
-        "4|     v     ☐   ",
-        "1|               ",
-        "5|               ",
-        "0|          v    ",
-        "5|               ",
-        "4|        •      ",
-        "3|               ",
-        "0|               ",
-        "0|               ",
-    ];    
-
-    // https://lukerissacher.com/battleships/PQhwIQIJJC-CEhAHAFMAI-AEAAcBFdAO-AAQABIAAAA-AI4AOQ
-    let _puzzle5 = vec![
-        "ships: 5sq x 1, 4sq x 2, 3sq x 3, ",
-        "       2sq x 4, 1sq x 4.",    
-        "  150405130033020",
-        "2|         •     ",
-        "3|               ",
-        "5|               ",
-        "4|               ",
-        "0|     v         ",
-        "3|   ~           ",
-        "1|               ",
-        "0|          ☐    ",
-        "4|        >      ",
-        "0|   ☐           ",
-        "1|               ",
-        "2|               ",
-        "0|               ",
-        "1|             ^ ",
-        "1|          v    ",
-    ];
-
-    let _puzzle6 = vec![
-        "ships: 4sq x 1, 3sq x 1, ",
-        "       2sq x 2, 1sq x 3.",
-        "  3141401",
-        "2|  ~    ",
-        "1|       ",
-        "4|       ",
-        "0|       ",
-        "1|       ",
-        "3|       ",
-        "3|       ",
-    ];
-
-    let _puzzle7 = vec![
-        "ships: 4sq x 1, 3sq x 1, ",
-        "       2sq x 2, 1sq x 3.",
-        "  1420213",
-        "2|       ",
-        "2|   <   ",
-        "1|       ",
-        "1|       ",
-        "3|       ",
-        "1|       ",
-        "3|       ",
-    ];
-
-    // https://lukerissacher.com/battleships/ICdJJIEkDo-Q4A4kg
-    let _puzzle8 = vec![
-        "ships: 4sq x 1, 3sq x 2, ",
-        "       2sq x 3, 1sq x 3.",
-        "  40405020",
-        "1|      ^ ",
-        "4|        ",
-        "2|        ",
-        "3|        ",
-        "1|  v     ",
-        "1|    v   ",
-        "0|      ^ ",
-        "3|        ",
-    ];
-
-    let _puzzle9 = vec![
-        "ships: 4sq x 1, 3sq x 2, ",
-        "       2sq x 3, 1sq x 3.",
-        "  60150213",
-        "3|        ",
-        "1|~       ",
-        "2|        ",
-        "4|     ~  ",
-        "2|        ",
-        "2|      ☐ ",
-        "3|        ",
-        "1|        ",
-    ];
-
-    let mut board = Board::new(&_puzzle9)?;
-
-    let is_solved = solve(&mut board)?;
-
-    board.print();
+    // --cnf:               Print the puzzle as a DIMACS CNF file, for an external SAT solver
+    // --sat-model FILE:    Read the SAT solver's output and print the solved board
+    // --search:            Solve by searching, instead of with the rules
+    // --rules-then-search: Solve with the rules, and search if they get stuck
+    // --probe [DEPTH]:     When the rules get stuck, look for contradictions up to DEPTH assumptions deep
+    // --simulate [DIFFICULTY] [GAMES]:
+    //                      Instead of solving, play games of classic Battleship between two AIs
+    // --serve [PORT]:      Host a Battleship match on localhost
+    // --serve-hidden [PORT]:
+    //                      Host a match where the players keep their fleets, and prove them at the end
+    // --connect ADDRESS [DIFFICULTY]:
+    //                      Join a Battleship match, and let the AI play
+    // --puzzle-from-game [SEED]:
+    //                      Play a game between two AIs, and turn the winner's fleet into a puzzle
+    // --tournament [round-robin|swiss] [GAMES] [PROGRAM...]:
+    //                      Play a tournament between the AIs, and any bot programs
+    // --bot [DIFFICULTY]:  Be a bot program for a tournament, on stdin/stdout
+    //
+    // Any of the solvers can also take:
+    // --puzzle FILE:       The puzzle to solve. Defaults to puzzles/puzzle9.txt.
+    //                      There are more in puzzles/, with their solutions.
+    // --events quiet|summary|verbose|json:
+    //                      How to report the solver's progress. Defaults to verbose.
+    // --style unicode|ascii:
+    //                      How to write boards. ASCII writes o for a dot and + for any middle.
+    //                      Defaults to unicode.
+    //
+    // And solving with the rules alone can take:
+    // --max-passes N:      Stop after N passes through the rules
+    // --timeout SECONDS:   Stop after this long
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let events = take_option(&mut args, "--events")?.unwrap_or_else(|| "verbose".to_string());
+    let style = match take_option(&mut args, "--style")? {
+        Some(style) => TextStyle::from_name(&style)?,
+        None        => TextStyle::Unicode,
+    };
+    let mut observer = observer_from_name(&events, style)?;
+
+    let mut board = match take_option(&mut args, "--puzzle")? {
+        Some(path) => {
+            let text = fs::read_to_string(&path)
+                .chain_err(|| format!("Unable to read the puzzle from {}", path))?;
+            parse_board(&text).chain_err(|| format!("Unable to parse the puzzle in {}", path))?
+        },
+        None => parse_board(include_str!("../puzzles/puzzle9.txt"))?,
+    };
+
+    let max_passes = match take_option(&mut args, "--max-passes")? {
+        Some(max_passes) => Some(max_passes.parse::<usize>()
+            .chain_err(|| format!("Invalid number of passes: {}", max_passes))?),
+        None => None,
+    };
+    let timeout = match take_option(&mut args, "--timeout")? {
+        Some(timeout) => Some(timeout.parse::<f64>().ok()
+            .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+            .ok_or_else(|| format!("Invalid timeout: {}", timeout))?),
+        None => None,
+    };
+
+    let solver: Solver = match args.first().map(String::as_str) {
+        Some("--cnf") => {
+            return write_dimacs(&board, &mut io::stdout());
+        },
+        Some("--sat-model") => {
+            let path = args.get(1).ok_or("--sat-model needs the path to a model file")?;
+            let model = fs::read_to_string(path)
+                .chain_err(|| format!("Unable to read SAT model from {}", path))?;
+
+            let solved = decode_sat_model(&board, &model)?;
+            solved.print(style);
+            return Ok(());
+        },
+        Some("--probe") => {
+            let depth = match args.get(1) {
+                Some(depth) => depth.parse::<usize>()
+                    .chain_err(|| format!("Invalid probe depth: {}", depth))?,
+                None => 1,
+            };
+
+            Box::new(move |board, observer| solve_with_probing(board, depth, observer))
+        },
+        Some("--simulate") => {
+            let difficulty = Difficulty::from_name(args.get(1).map_or("density", String::as_str))?;
+            let num_games = match args.get(2) {
+                Some(num_games) => num_games.parse::<usize>()
+                    .chain_err(|| format!("Invalid number of games: {}", num_games))?,
+                None => 100,
+            };
+
+            let report = simulate([difficulty, difficulty], standard_layout(), &standard_fleet(), num_games, 0)?;
+            println!("Played {} games at {:?} difficulty", report.num_games, difficulty);
+            println!("Average shots to win: {:.1}", report.average_shots_to_win);
+            return Ok(());
+        },
+        Some(arg @ "--serve") | Some(arg @ "--serve-hidden") => {
+            let port = match args.get(1) {
+                Some(port) => port.parse::<u16>()
+                    .chain_err(|| format!("Invalid port: {}", port))?,
+                None => DEFAULT_PORT,
+            };
+
+            let listener = std::net::TcpListener::bind(("127.0.0.1", port))
+                .chain_err(|| format!("Unable to listen on port {}", port))?;
+            println!("Waiting for players on port {}", port);
+
+            let report = if arg == "--serve" {
+                serve_match(&listener, standard_layout(), &standard_fleet())?
+            }
+            else {
+                serve_hidden_match(&listener, standard_layout(), &standard_fleet())?
+            };
+            for (name, &cheated) in report.names.iter().zip(report.cheated.iter()) {
+                if cheated {
+                    println!("{} cheated", name);
+                }
+            }
+            println!("{} won in {} shots", report.names[report.winner], report.shots[report.winner]);
+            return Ok(());
+        },
+        Some("--connect") => {
+            let address = args.get(1).ok_or("--connect needs the address of a server")?;
+            let difficulty = Difficulty::from_name(args.get(2).map_or("density", String::as_str))?;
+
+            let won = play_as_ai(address, "communicator", difficulty, 0)?;
+            println!("{}", if won { "Won 😀" } else { "Lost 😞" });
+            return Ok(());
+        },
+        Some("--puzzle-from-game") => {
+            let seed = match args.get(1) {
+                Some(seed) => seed.parse::<u64>()
+                    .chain_err(|| format!("Invalid seed: {}", seed))?,
+                None => 0,
+            };
+
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            let game = play_ai_game([Difficulty::Density, Difficulty::Density], standard_layout(), &standard_fleet(), &mut rng)?;
+            for line in puzzle_from_game(&game, &mut rng)? {
+                println!("{}", line);
+            }
+            return Ok(());
+        },
+        Some("--tournament") => {
+            let pairing = match args.get(1).map_or("round-robin", String::as_str) {
+                "round-robin" => Pairing::RoundRobin,
+                "swiss"       => Pairing::Swiss { rounds: 3 },
+                pairing       => bail!("Unknown pairing: {}", pairing),
+            };
+            let games_per_match = match args.get(2) {
+                Some(num_games) => num_games.parse::<usize>()
+                    .chain_err(|| format!("Invalid number of games: {}", num_games))?,
+                None => 10,
+            };
+
+            let mut bots: Vec<Box<dyn Strategy>> = vec![
+                Box::new(AiStrategy::new(Difficulty::Random, 1)),
+                Box::new(AiStrategy::new(Difficulty::Parity, 2)),
+                Box::new(AiStrategy::new(Difficulty::Density, 3)),
+            ];
+            for program in args.iter().skip(3) {
+                bots.push(Box::new(ExternalStrategy::new(program, program, &[])));
+            }
+
+            let config = TournamentConfig { pairing, games_per_match, ..TournamentConfig::default() };
+            print!("{}", run_tournament(&mut bots, &config)?);
+            return Ok(());
+        },
+        Some("--bot") => {
+            let difficulty = Difficulty::from_name(args.get(1).map_or("density", String::as_str))?;
+            let stdin = io::stdin();
+            return play_as_bot(difficulty, 0, stdin.lock(), io::stdout());
+        },
+        Some("--search")            => Box::new(|board, _| solve_by_search(board)),
+        Some("--rules-then-search") => Box::new(solve_with_search),
+        Some(arg)                   => bail!("Unknown argument: {}", arg),
+        None => Box::new(move |board, observer| {
+            let config = SolveConfig {
+                max_passes,
+                deadline: timeout.map(|timeout| Instant::now() + timeout),
+                ..Default::default()
+            };
+
+            let result = solve_with_config(board.clone(), config, observer)?;
+            *board = result.board;
+
+            match result.outcome {
+                SolveOutcome::Invalid(error) | SolveOutcome::Contradiction(error) => bail!(error),
+                SolveOutcome::Solved  => Ok(true),
+                SolveOutcome::Stalled => Ok(false),
+                outcome => {
+                    eprintln!("Stopped early after {} passes: {:?}", result.passes, outcome);
+                    Ok(false)
+                },
+            }
+        }),
+    };
+
+    // Say so when the fleet is a guess. stderr keeps JSON output clean.
+    if *board.fleet_source() != FleetSource::Given {
+        eprintln!("note: {}", board.fleet_source());
+    }
+
+    let is_solved = solver(&mut board, observer.as_mut())?;
+
+    // Keep JSON output machine-readable. The finished event has the board.
+    if events == "json" {
+        return Ok(());
+    }
+
+    board.print(style);
 
     if is_solved {
         println!("Solved 😀");
@@ -171,6 +233,19 @@ fn run() -> Result<()> {
     Ok(())
 }
 
+// Remove `--name VALUE` from the args, and return the value
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>> {
+    match args.iter().position(|arg| arg == name) {
+        Some(idx) => {
+            ensure!(idx + 1 < args.len(), "{} needs a value", name);
+            let value = args.remove(idx + 1);
+            args.remove(idx);
+            Ok(Some(value))
+        },
+        None => Ok(None),
+    }
+}
+
 fn main() {
     if let Err(ref e) = run() {
         use std::io::Write;
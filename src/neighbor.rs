@@ -1,7 +1,7 @@
 use std::collections::HashSet;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
-pub enum Neighbor {
+pub(crate) enum Neighbor {
     N, NE, E, SE, S, SW, W, NW
 }
 
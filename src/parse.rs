@@ -1,27 +1,25 @@
 #![allow(clippy::double_comparisons)] // parse! macros trigger this
 
 use std::collections::HashMap;
+use std::fmt;
 
 use crate::board::*;
+use crate::ship::*;
 use crate::square::*;
 
 use nom::*;
 
-use std;
 
 /////////////////////////////////////////////////////////////////////
 //
 // Utility functions
 
 fn is_digit(c: char) -> bool {
-  match c {
-    '0'..='9' => true,
-    _ => false,
-  }
+  c.is_ascii_digit()
 }
 
 fn int_from_digit(input: &str) -> std::result::Result<usize, std::num::ParseIntError> {
-  usize::from_str_radix(input, 10)
+  input.parse::<usize>()
 }
 
 /////////////////////////////////////////////////////////////////////
@@ -34,27 +32,76 @@ struct ShipToFind {
 	count: usize,
 }
 
+// A number with any number of digits. Unlike nom's digit parsers, this
+// doesn't need more input to know that the number has ended.
+fn number(input: &str) -> nom::IResult<&str, usize> {
+	let num_digits = input.find(|ch: char| !is_digit(ch)).unwrap_or(input.len());
+
+	match int_from_digit(&input[.. num_digits]) {
+		Ok(number) => Ok((&input[num_digits ..], number)),
+		Err(_)     => Err(nom::Err::Error(error_position!(input, nom::ErrorKind::Digit))),
+	}
+}
+
+// There's no such thing as a ship with no squares
+named!(ship_size<&str, usize>,
+	verify!(number, |size: usize| size > 0)
+);
+
 named!(ship_to_find<&str, ShipToFind>,
 	do_parse!(
-		size: map_res!(take_while_m_n!(1, 1, is_digit), int_from_digit) >>
+		size: ship_size >>
 			ws!(
 				tuple!(
 		      		tag!("sq"),
 		      		tag!("x")
 		      	)
 		    ) >>
-		count: map_res!(take_while_m_n!(1, 1, is_digit), int_from_digit) >>
+		count: number >>
 		(ShipToFind { size, count })
 	)
 );
 
+fn named_fleet(name: &str) -> Option<Vec<ShipToFind>> {
+	let ships = standard_puzzle_fleet(name)?.iter()
+		.map(|&(size, count)| ShipToFind { size, count })
+		.collect();
+
+	Some(ships)
+}
+
+fn is_fleet_name_char(c: char) -> bool {
+	c.is_ascii_alphanumeric()
+}
+
+named!(standard_fleet_name<&str, Vec<ShipToFind>>,
+	map_opt!(
+		preceded!(tag!("standard-"), take_while1!(is_fleet_name_char)),
+		named_fleet
+	)
+);
+
+named!(ship_list<&str, Vec<ShipToFind>>,
+	separated_nonempty_list_complete!(
+		ws!(tag!(",")),
+		ship_to_find
+	)
+);
+
+// Not alt!, so that errors in the list say where they are
+fn fleet(input: &str) -> nom::IResult<&str, Vec<ShipToFind>> {
+	if input.starts_with("standard-") {
+		standard_fleet_name(input)
+	}
+	else {
+		ship_list(input)
+	}
+}
+
 named!(ships_to_find<&str, Vec<ShipToFind>>,
 	do_parse!(
 		tag!("ships:") >>
-		ships: ws!(separated_nonempty_list_complete!(
-				ws!(tag!(",")),
-				ship_to_find
-		)) >>
+		ships: ws!(fleet) >>
 		tag!(".\n") >>
 		(ships)
 	)
@@ -69,7 +116,7 @@ mod test_ships_to_find {
     fn it_parses_individual_ship() -> std::result::Result<(), nom::Err<&'static str>> {
     	let text = "4sq x 2";
 
-    	let (remaining, ship) = ship_to_find(&text)?;
+    	let (remaining, ship) = ship_to_find(text)?;
         assert_eq!(remaining, "");
 
         assert_eq!(ship.size, 4);
@@ -82,7 +129,7 @@ mod test_ships_to_find {
     fn it_parses_1_ship() -> std::result::Result<(), nom::Err<&'static str>> {
     	let text = "ships: 5sq x 1.\n";
 
-    	let (remaining, ships) = ships_to_find(&text)?;
+    	let (remaining, ships) = ships_to_find(text)?;
         assert_eq!(remaining, "");
 
         assert_eq!(ships.len(), 1);
@@ -97,7 +144,7 @@ mod test_ships_to_find {
     fn it_parses_2_ships() -> std::result::Result<(), nom::Err<&'static str>>  {
     	let text = "ships: 5sq x 1, 4sq x 2.\n";
 
-    	let (remaining, ships) = ships_to_find(&text)?;
+    	let (remaining, ships) = ships_to_find(text)?;
         assert_eq!(remaining, "");
 
         assert_eq!(ships.len(), 2);
@@ -115,7 +162,7 @@ mod test_ships_to_find {
     fn it_parses_5_ships() -> std::result::Result<(), nom::Err<&'static str>>  {
         let text = "ships: 5sq x 1, 4sq x 1, 3sq x 2, 2sq x 3, 1sq x 4.\n";
 
-        let (remaining, ships) = ships_to_find(&text)?;
+        let (remaining, ships) = ships_to_find(text)?;
         assert_eq!(remaining, "");
 
         assert_eq!(ships.len(), 5);
@@ -142,7 +189,7 @@ mod test_ships_to_find {
     fn it_parses_ships_on_multiple_lines() -> std::result::Result<(), nom::Err<&'static str>> {
     	let text = "ships: 5sq x 1,\n\t4sq x 2.\n";
 
-    	let (remaining, ships) = ships_to_find(&text)?;
+    	let (remaining, ships) = ships_to_find(text)?;
         assert_eq!(remaining, "");
 
     	assert_eq!(ships.len(), 2);
@@ -156,6 +203,39 @@ mod test_ships_to_find {
         Ok(())
     }
 
+    #[test]
+    fn it_parses_multi_digit_ships() -> std::result::Result<(), nom::Err<&'static str>> {
+        let text = "ships: 12sq x 1, 1sq x 10.\n";
+
+        let (remaining, ships) = ships_to_find(text)?;
+        assert_eq!(remaining, "");
+
+        assert_eq!(ships.len(), 2);
+
+        assert_eq!(ships[0].size, 12);
+        assert_eq!(ships[0].count, 1);
+
+        assert_eq!(ships[1].size, 1);
+        assert_eq!(ships[1].count, 10);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_parses_standard_fleets() -> std::result::Result<(), nom::Err<&'static str>> {
+        let (remaining, ships) = ships_to_find("ships: standard-10x10.\n")?;
+        assert_eq!(remaining, "");
+
+        let ships = ships.iter()
+            .map(|ship| (ship.size, ship.count))
+            .collect::<Vec<_>>();
+        assert_eq!(ships, [(4, 1), (3, 2), (2, 3), (1, 4)]);
+
+        assert!(ships_to_find("ships: standard-7x7.\n").is_err());
+
+        Ok(())
+    }
+
 }
 
 /////////////////////////////////////////////////////////////////////
@@ -174,7 +254,8 @@ named!(header_items<&str, Vec<usize>>,
 
 named!(header<&str, Vec<usize>>,
 	do_parse!(
-		tag!("  ")           >>
+		tag!(" ")            >>
+		tag!(" ")            >>
 		counts: header_items >>
 		tag!("\n")           >>
 		(counts)
@@ -229,7 +310,7 @@ named!(ships_remaining<&str, usize>,
 );
 
 named!(square<&str, Square>, 
-	map_res!(take_s!(1), square_from_char)
+	map_res!(take!(1), square_from_char)
 );
 
 named!(squares<&str, Vec<Square>>, 
@@ -311,19 +392,383 @@ mod board_body_tests {
 }
 
 
+/////////////////////////////////////////////////////////////////////
+//
+// Parse errors
+
+// Where the board text went wrong. Lines and columns count from 1, and
+// columns count characters, not bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+	pub line: usize,
+	pub column: usize,
+
+	// None at the end of the text
+	pub found: Option<char>,
+
+	pub expected: String,
+}
+
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "Line {}, column {}: expected {}, but found ", self.line, self.column, self.expected)?;
+
+		match self.found {
+			Some('\n')  => write!(f, "the end of the line"),
+			Some(found) => write!(f, "{:?}", found),
+			None        => write!(f, "the end of the text"),
+		}
+	}
+}
+
+impl ParseError {
+	// An error at `position`, which is a suffix of `text`
+	fn at(text: &str, position: &str, expected: impl Into<String>) -> Self {
+		let (line, column) = line_and_column(text, position);
+
+		ParseError {
+			line,
+			column,
+			found: position.chars().next(),
+			expected: expected.into(),
+		}
+	}
+}
+
+// Where `position`, a suffix of `text`, starts
+fn line_and_column(text: &str, position: &str) -> (usize, usize) {
+	let before = &text[.. text.len() - position.len()];
+	let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+	(before.matches('\n').count() + 1, before[line_start ..].chars().count() + 1)
+}
+
+impl From<ParseError> for crate::error::Error {
+	fn from(error: ParseError) -> Self {
+		crate::error::ErrorKind::Parse(error).into()
+	}
+}
+
+// The characters that Square::from_char understands, for error messages
+fn square_chars() -> String {
+	let mut chars = String::new();
+	for style in [TextStyle::Unicode, TextStyle::Ascii].iter() {
+		let squares = [Square::Unknown, Square::Water].iter().cloned()
+			.chain(ShipSquare::all().map(Square::ShipSquare));
+
+		for square in squares {
+			let square_char = square.to_char(*style);
+			if !chars.contains(square_char) {
+				chars.push(square_char);
+			}
+		}
+	}
+
+	chars
+}
+
+// Where a nom parser stopped, or None if it ran out of input
+fn failed_at<'a>(error: &nom::Err<&'a str>) -> Option<&'a str> {
+	match error {
+		nom::Err::Error(nom::Context::Code(position, _)) |
+		nom::Err::Failure(nom::Context::Code(position, _)) => Some(position),
+		nom::Err::Incomplete(_) => None,
+	}
+}
+
+/////////////////////////////////////////////////////////////////////
+//
+// Normalize the text
+//
+// Hand-edited puzzles can have comments, blank lines, stray whitespace,
+// CRLF line endings, a right-hand border, and no "." at the end of the
+// ships or the board. Rewrite them into the strict format that the parsers
+// above expect.
+
+// A line of the strict format, and where each of its characters came from
+struct NormalLine {
+	text: String,
+	line: usize,
+	columns: Vec<usize>,
+	end_column: usize,
+}
+
+impl NormalLine {
+	fn new(line: usize) -> Self {
+		NormalLine { text: String::new(), line, columns: Vec::new(), end_column: 1 }
+	}
+
+	fn push(&mut self, ch: char, column: usize) {
+		self.text.push(ch);
+		self.columns.push(column);
+		self.end_column = column + 1;
+	}
+
+	fn extend(&mut self, chars: &[(usize, char)]) {
+		for &(column, ch) in chars {
+			self.push(ch, column);
+		}
+	}
+
+	// The original column of the character at `index`, counting from 0
+	fn column(&self, index: usize) -> usize {
+		self.columns.get(index).cloned().unwrap_or(self.end_column)
+	}
+}
+
+impl ParseError {
+	// Move an error in the normalized text back to where it was in the original
+	fn in_original(self, lines: &[NormalLine]) -> Self {
+		let normal = &lines[self.line.min(lines.len()) - 1];
+
+		ParseError {
+			line: normal.line,
+			column: normal.column(self.column - 1),
+			..self
+		}
+	}
+}
+
+enum Section {
+	Start,
+	MoreShips,
+	Header,
+	Rows { num_cols: usize },
+	Done,
+}
+
+fn is_blank(chars: &[(usize, char)]) -> bool {
+	chars.iter().all(|&(_, ch)| ch.is_whitespace())
+}
+
+fn normalize(text: &str) -> std::result::Result<Vec<NormalLine>, ParseError> {
+	let mut lines = Vec::new();
+	let mut section = Section::Start;
+	let mut num_lines = 0;
+	let mut has_rows = false;
+
+	for (index, line_text) in text.split('\n').enumerate() {
+		let line = index + 1;
+		num_lines = line;
+
+		let line_text = line_text.strip_suffix('\r').unwrap_or(line_text);
+		let content = line_text.split('#').next().unwrap_or("");
+
+		// Columns count from 1
+		let chars = content.chars()
+			.enumerate()
+			.map(|(index, ch)| (index + 1, ch))
+			.collect::<Vec<_>>();
+		let start = match chars.iter().position(|&(_, ch)| !ch.is_whitespace()) {
+			Some(start) => start,
+			None        => continue,
+		};
+		let end = chars.iter().rposition(|&(_, ch)| !ch.is_whitespace()).unwrap_or(start) + 1;
+		let trimmed = &chars[start .. end];
+
+		let is_ships = match section {
+			Section::Start     => content.trim_start().starts_with("ships:"),
+			Section::MoreShips => true,
+			_                  => false,
+		};
+
+		let mut normal = NormalLine::new(line);
+		match section {
+			Section::Start | Section::MoreShips if is_ships => {
+				normal.extend(trimmed);
+				section = match trimmed.last().map(|&(_, ch)| ch) {
+					Some(',') => Section::MoreShips,
+					Some('.') => Section::Header,
+					_ => {
+						// The "." at the end of the ships is optional
+						normal.push('.', normal.end_column);
+						Section::Header
+					},
+				};
+			},
+			Section::Start | Section::MoreShips | Section::Header => {
+				// Line the counts up with the squares
+				normal.push(' ', trimmed[0].0);
+				normal.push(' ', trimmed[0].0);
+				normal.extend(trimmed);
+				section = Section::Rows { num_cols: trimmed.len() };
+			},
+			Section::Rows { .. } if trimmed.len() == 1 && trimmed[0].1 == '.' => {
+				normal.extend(trimmed);
+				section = Section::Done;
+			},
+			Section::Rows { num_cols } => {
+				normal_row(&mut normal, &chars[start ..], num_cols);
+				has_rows = true;
+			},
+			Section::Done => return Err(ParseError {
+				line,
+				column: trimmed[0].0,
+				found: Some(trimmed[0].1),
+				expected: "the end of the board, after the \".\"".to_string(),
+			}),
+		}
+
+		lines.push(normal);
+	}
+
+	match section {
+		// Leave it to the parser to complain about, after the header
+		Section::Rows { .. } if !has_rows => {},
+		Section::Rows { .. } => {
+			let mut terminator = NormalLine::new(num_lines);
+			terminator.push('.', 1);
+			lines.push(terminator);
+		},
+		Section::Done => {},
+		_ => return Err(ParseError {
+			line: num_lines,
+			column: text.rsplit('\n').next().unwrap_or("").chars().count() + 1,
+			found: None,
+			expected: "the column header, with a count of ship squares for each col".to_string(),
+		}),
+	}
+
+	Ok(lines)
+}
+
+// A row is its count, an optional space, "|", and its squares. The squares
+// can be followed by a right-hand border and whitespace. Trailing spaces
+// inside the border are unknown squares, so they're kept.
+fn normal_row(normal: &mut NormalLine, chars: &[(usize, char)], num_cols: usize) {
+	let num_digits = chars.iter().take_while(|&&(_, ch)| ch.is_ascii_digit()).count();
+	normal.extend(&chars[.. num_digits]);
+	let rest = &chars[num_digits ..];
+
+	let num_spaces = rest.iter().take_while(|&&(_, ch)| ch.is_whitespace()).count();
+	if rest.get(num_spaces).map(|&(_, ch)| ch) != Some('|') {
+		// Leave it to the parser to complain about
+		normal.extend(rest);
+		return;
+	}
+
+	normal.extend(&rest[num_spaces .. num_spaces + 1]);
+	let mut squares = &rest[num_spaces + 1 ..];
+
+	if squares.len() > num_cols {
+		let after = &squares[num_cols ..];
+		let after = if after[0].1 == '|' { &after[1 ..] } else { after };
+		if is_blank(after) {
+			squares = &squares[.. num_cols];
+		}
+	}
+
+	normal.extend(squares);
+}
+
 /////////////////////////////////////////////////////////////////////
 //
 // Parse the entire board
 
-named!(board<&str, crate::error::Result<Board>>,
-	do_parse!(
-		ships_to_find:           opt!(ships_to_find) >>
-		ships_remaining_for_col: header >>
-		rows:                    rows   >>
-		                         tag!(".") >>
-		(make_board(ships_to_find, ships_remaining_for_col, rows))
-	)
-);
+// Feeds the text through the parsers above, one part of the board at a
+// time, so that errors can say where they happened
+struct BoardParser<'a> {
+	text: &'a str,
+	input: &'a str,
+}
+
+impl<'a> BoardParser<'a> {
+	fn line(&self) -> usize {
+		line_and_column(self.text, self.input).0
+	}
+
+	// Run `parser`, and if it fails, ask `expected` what should have been
+	// in the column where it stopped, counting from 0
+	fn parse<T>(&mut self,
+		parser: impl Fn(&'a str) -> nom::IResult<&'a str, T>,
+		expected: impl Fn(usize) -> String)
+		-> std::result::Result<T, ParseError> {
+
+		match parser(self.input) {
+			Ok((rest, value)) => {
+				self.input = rest;
+				Ok(value)
+			},
+			Err(error) => match failed_at(&error) {
+				Some(position) => {
+					let column = line_and_column(self.text, position).1 - 1;
+					Err(ParseError::at(self.text, position, expected(column)))
+				},
+				None => Err(ParseError::at(self.text, &self.text[self.text.len() ..],
+					"more lines, and then \".\" to end the board")),
+			},
+		}
+	}
+
+	fn error(&self, expected: &str) -> ParseError {
+		ParseError::at(self.text, self.input, expected)
+	}
+}
+
+pub fn parse_board(text: &str) -> crate::error::Result<Board> {
+	let lines = normalize(text)?;
+	let normal_text = lines.iter()
+		.map(|normal| normal.text.as_str())
+		.collect::<Vec<_>>()
+		.join("\n");
+
+	let (ships_to_find, ships_remaining_for_col, rows) = parse_normal_board(&normal_text)
+		.map_err(|error| error.in_original(&lines))?;
+
+	make_board(ships_to_find, ships_remaining_for_col, rows)
+}
+
+type NormalBoard = (Option<Vec<ShipToFind>>, Vec<usize>, Vec<Row>);
+
+fn parse_normal_board(text: &str) -> std::result::Result<NormalBoard, ParseError> {
+	let mut parser = BoardParser { text, input: text };
+
+	let ships_to_find = if parser.input.starts_with("ships:") {
+		Some(parser.parse(ships_to_find,
+			|_| "the ships: syntax, like \"ships: 4sq x 1, 3sq x 2.\" or \"ships: standard-10x10.\"".to_string())?)
+	}
+	else {
+		None
+	};
+
+	let ships_remaining_for_col = parser.parse(header, |_| "a digit, or the end of the line".to_string())?;
+
+	let mut rows = Vec::new();
+	while !parser.input.starts_with('.') {
+		if parser.input.is_empty() {
+			return Err(parser.error("a row, or \".\" to end the board"));
+		}
+
+		let line = parser.line();
+		let row = parser.parse(row, |column| match column {
+			0 => "a digit".to_string(),
+			1 => "\"|\"".to_string(),
+			_ => format!("one of the square characters {:?}, or the end of the row", square_chars()),
+		})?;
+
+		// Ragged rows are reported at the first square that's out of line
+		let num_cols = ships_remaining_for_col.len();
+		if row.squares.len() != num_cols {
+			let line_text = text.lines().nth(line - 1).unwrap_or("");
+
+			return Err(ParseError {
+				line,
+				column: num_cols.min(row.squares.len()) + 3,
+				found: line_text.chars().nth(num_cols + 2).or(Some('\n')),
+				expected: format!("row {} to have {} squares, like the header, not {}",
+					rows.len(), num_cols, row.squares.len()),
+			});
+		}
+
+		rows.push(row);
+	}
+
+	if rows.is_empty() {
+		return Err(parser.error("at least one row, under the header"));
+	}
+
+	Ok((ships_to_find, ships_remaining_for_col, rows))
+}
 
 fn make_board(
     ships_to_find_vec: Option<Vec<ShipToFind>>, 
@@ -331,22 +776,15 @@ fn make_board(
     rows: Vec<Row>) 
     -> crate::error::Result<Board> {
 
+	let has_ships_line = ships_to_find_vec.is_some();
+
 	// Convert ships_to_find from vector to hash map
     let ships_to_find = ships_to_find_vec
-        .unwrap_or_else(|| vec![])
+        .unwrap_or_default()
         .iter()
         .map(|ship| (ship.size, ship.count))
         .collect::<HashMap<_, _>>();
 
-	// Ensure all rows have the same number of cols
-	let num_cols = ships_remaining_for_col.len();
-	let consistent_num_cols = rows.iter().all(|row| {
-		row.squares.len() == num_cols
-	});
-	if !consistent_num_cols {
-		bail!("Rows have inconsistent number of columns");
-	}
-
 	let ships_remaining_for_row = rows.iter()
 		.map(|row| row.ships_remaining)
 		.collect::<Vec<_>>();
@@ -355,22 +793,18 @@ fn make_board(
 		.map(|row| row.squares.clone())
 		.collect::<Vec<_>>();
 
-	let board = Board::new_from_data(
+	let mut board = Board::new_from_data(
 		squares,
 		ships_remaining_for_row,
 		ships_remaining_for_col,
 		ships_to_find
-	);
+	)?;
 
-    Ok(board)
-}
-
-pub fn parse_board(text: &str) -> crate::error::Result<Board> {
-	match board(text) {
-		Ok(("", board)) => board,
-		Ok((_,  _))     => bail!("Had leftover information"),
-		Err(_)          => bail!("Unable to parse the board"),
+	if !has_ships_line {
+		board.infer_fleet();
 	}
+
+	Ok(board)
 }
 
 #[cfg(test)]
@@ -421,5 +855,130 @@ mod board_tests {
 
         Ok(())
     }    
-}
 
+    fn parse_error(lines: &[&str]) -> ParseError {
+        match parse_board(&lines.join("\n")) {
+            Err(crate::error::Error(crate::error::ErrorKind::Parse(error), _)) => error,
+            Err(error) => panic!("Expected a parse error, but got: {}", error),
+            Ok(_)      => panic!("Expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn it_locates_bad_squares() {
+        let error = parse_error(&[
+            "  123",
+            "1|~  ",
+            "2|•x*",
+            ".",
+        ]);
+
+        assert_eq!(error, ParseError {
+            line: 3,
+            column: 4,
+            found: Some('x'),
+            expected: "one of the square characters \" ~*<>^v|-☐•+o\", or the end of the row".to_string(),
+        });
+    }
+
+    #[test]
+    fn it_explains_what_was_expected() {
+        assert_eq!(parse_error(&["  12", "x|  ", "."]).to_string(),
+            "Line 2, column 1: expected a digit, but found 'x'");
+        assert_eq!(parse_error(&["  12", "1   ", "."]).to_string(),
+            "Line 2, column 2: expected \"|\", but found ' '");
+        assert_eq!(parse_error(&["  1x", "1|  ", "."]).to_string(),
+            "Line 1, column 4: expected a digit, or the end of the line, but found 'x'");
+        assert_eq!(parse_error(&["  12", "1|  ", ".", "extra"]).to_string(),
+            "Line 4, column 1: expected the end of the board, after the \".\", but found 'e'");
+        assert_eq!(parse_error(&["# Nothing but a comment", ""]).to_string(),
+            "Line 2, column 1: expected the column header, with a count of ship squares for each col, but found the end of the text");
+    }
+
+    #[test]
+    fn it_explains_bad_ships() {
+        let error = parse_error(&["ships: 4sq y 1.", "  1", "1| ", "."]);
+
+        assert_eq!((error.line, error.column, error.found), (1, 12, Some('y')));
+        assert!(error.expected.starts_with("the ships: syntax"));
+    }
+
+    #[test]
+    fn it_names_ragged_rows() {
+        assert_eq!(parse_error(&["  12", "1|  ", "0|  ~", "."]).to_string(),
+            "Line 3, column 5: expected row 1 to have 2 squares, like the header, not 3, but found '~'");
+        assert_eq!(parse_error(&["  12", "1| ", "."]).to_string(),
+            "Line 2, column 4: expected row 0 to have 2 squares, like the header, not 1, but found the end of the line");
+    }
+
+    #[test]
+    fn it_accepts_hand_edited_text() -> crate::error::Result<()> {
+        let strict = [
+            "ships: 2sq x 1, 1sq x 1.",
+            "  102",
+            "2|  *",
+            "1|   ",
+        ];
+        let hand_edited = [
+            "# A small puzzle",
+            "ships: 2sq x 1,",
+            "   1sq x 1.   ",
+            "",
+            "102      # cols",
+            "  2 |  *|",
+            "",
+            "1|   |  # trailing spaces are unknown squares",
+            ".",
+            "# the end",
+        ].join("\r\n");
+
+        assert_eq!(parse_board(&hand_edited)?.to_strings(), strict);
+
+        // The terminator is optional, and so is the right-hand border
+        assert_eq!(parse_board(&strict.join("\n"))?.to_strings(), strict);
+        assert_eq!(parse_board("  1\n1| |\n")?.to_strings(), ["  1", "1| "]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_locates_errors_in_hand_edited_text() {
+        let error = parse_error(&[
+            "# A comment",
+            "",
+            "  12",
+            "   1 |~x   # comment",
+        ]);
+
+        assert_eq!(error.to_string(),
+            "Line 4, column 8: expected one of the square characters \" ~*<>^v|-☐•+o\", or the end of the row, but found 'x'");
+    }
+
+    #[test]
+    fn it_accepts_ships_without_a_period() -> crate::error::Result<()> {
+        let board = parse_board("ships: standard-6x6\n  1\n1| ")?;
+
+        assert_eq!(board.to_strings()[0], "ships: 3sq x 1, 2sq x 2, 1sq x 3.");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_unknown_standard_fleets() {
+        let error = parse_error(&["ships: standard-7x7.", "  1", "1| "]);
+
+        assert_eq!((error.line, error.column, error.found), (1, 8, Some('s')));
+        assert!(error.expected.contains("standard-10x10"));
+    }
+
+    #[test]
+    fn it_rejects_boards_that_cannot_exist() {
+        assert_eq!(parse_error(&["  12", "."]).to_string(),
+            "Line 2, column 1: expected at least one row, under the header, but found '.'");
+        assert_eq!(parse_error(&["  12", ""]).to_string(),
+            "Line 1, column 5: expected more lines, and then \".\" to end the board, but found the end of the text");
+
+        let error = parse_error(&["ships: 0sq x 3.", "  1", "1| "]);
+        assert_eq!((error.line, error.column, error.found), (1, 8, Some('0')));
+    }
+}
@@ -17,7 +17,7 @@ impl ExpectedShip {
 
 impl fmt::Display for ExpectedShip {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        return write!(f, "{}sq", self.size)
+        write!(f, "{}sq", self.size)
     }
 }
 
@@ -27,8 +27,18 @@ impl From<usize> for ExpectedShip {
     }
 }
 
+// The fleets that puzzles of these sizes usually have, as (ship size, count)
+pub fn standard_puzzle_fleet(name: &str) -> Option<&'static [(usize, usize)]> {
+    match name {
+        "6x6"   => Some(&[(3, 1), (2, 2), (1, 3)]),
+        "10x10" => Some(&[(4, 1), (3, 2), (2, 3), (1, 4)]),
+        "15x15" => Some(&[(5, 1), (4, 2), (3, 3), (2, 4), (1, 5)]),
+        _       => None,
+    }
+}
+
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
-pub struct ShipHead<'a> {
+pub(crate) struct ShipHead<'a> {
     pub origin: Coord<'a>,
 
     // constant axis: The one that remains the same as we increment through coordinats
@@ -46,13 +56,12 @@ impl<'a> ShipHead<'a> {
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
-pub struct Ship<'a> {
+pub(crate) struct Ship<'a> {
     pub head: ShipHead<'a>,
     pub size: usize,
 }
 
 impl<'a> Ship<'a> {    
-    #[allow(dead_code)] // currently, this is only used in tests    
     pub fn new(origin: Coord<'a>, incrementing_axis: Axis, size: usize) -> Self {
         Ship {
             head: ShipHead {
@@ -102,7 +111,7 @@ impl<'a> Ship<'a> {
             ShipSquare::Dot
         }
         else {
-            enum Position { Start, Middle, End };
+            enum Position { Start, Middle, End }
             let pos = if square_idx == 0             { Position::Start  }
                 else  if square_idx == self.size - 1 { Position::End    }
                 else                                 { Position::Middle };
@@ -136,7 +145,7 @@ mod layout_tests {
             },
             size: 3
         };
-        assert_eq!(ship.is_in_bounds(), true);
+        assert!(ship.is_in_bounds());
 
         // Out of bounds
         let ship = Ship {
@@ -145,7 +154,7 @@ mod layout_tests {
             },
             size: 5
         };
-        assert_eq!(ship.is_in_bounds(), false);
+        assert!(!ship.is_in_bounds());
 
     }
 }    
@@ -1,7 +1,10 @@
-use itertools::Itertools;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 
 use crate::board::*;
 use crate::error::*;
+use crate::layout::*;
+use crate::validate::validate_board;
 
 mod fill_unknown;
 mod surround_with_water;
@@ -11,42 +14,459 @@ mod only_place_it_can_go;
 mod specify_middles;
 mod surround_middles;
 mod enough_space_for_middle;
+mod fleet_in_segments;
+mod line_solver;
+mod search;
+mod probe;
+mod events;
+mod config;
+
+pub use self::search::{solve_by_search, count_solutions};
+pub use self::probe::*;
+pub use self::events::*;
+pub use self::config::*;
+
+// A rule, along with what it looks at. The solver keeps track of when each
+// rule last ran, and only runs it again where something it looks at has
+// changed since then. Anywhere else, it would find what it found last time.
+#[derive(Clone, Copy)]
+pub(crate) enum Rule {
+    // Looks at one square at a time, and the squares around it
+    Neighbors(fn(&mut Board, Coord) -> Result<()>),
+
+    // Looks at one square at a time, the rest of its row and col, and their counts
+    RowAndCol(fn(&mut Board, Coord) -> Result<()>),
+
+    // Looks at one row/col at a time, its count, and the rows/cols either side of it
+    Lines(fn(&mut Board, RowOrCol) -> Result<()>),
+
+    // Looks at the whole board
+    Board(fn(&mut Board) -> Result<()>),
+}
+
+impl Rule {
+    // Run the rule over the whole board
+    pub fn apply(self, board: &mut Board) -> Result<()> {
+        self.apply_since(board, None)
+    }
+
+    // Run the rule only where something it looks at has changed since the
+    // given stamp, or everywhere if there isn't one. That includes changes the
+    // rule makes as it goes, so it finds everything a run over the whole board would.
+    fn apply_since(self, board: &mut Board, since: Option<usize>) -> Result<()> {
+        let layout = board.layout;
+
+        match self {
+            Rule::Neighbors(rule) => match since {
+                // Past a point, queueing the squares near each change costs more than looking at every square
+                Some(since) if (board.stamp() - since) * 9 < layout.num_rows * layout.num_cols => {
+                    apply_near_changes(board, rule, since)?
+                },
+                _ => {
+                    for coord in layout.all_coordinates() {
+                        rule(board, coord)?;
+                    }
+                },
+            },
+            Rule::RowAndCol(rule) => {
+                for coord in layout.all_coordinates() {
+                    if since.is_none_or(|since|
+                        board.changed_in(coord.row(), since) || board.changed_in(coord.col(), since)) {
+                        rule(board, coord)?;
+                    }
+                }
+            },
+            Rule::Lines(rule) => {
+                for row_or_col in layout.rows_and_cols() {
+                    if since.is_none_or(|since| board.changed_near_line(row_or_col, since)) {
+                        rule(board, row_or_col)?;
+                    }
+                }
+            },
+            Rule::Board(rule) => {
+                if since.is_none_or(|since| board.stamp() > since) {
+                    rule(board)?;
+                }
+            },
+        }
+
+        Ok(())
+    }
+}
+
+// Run a rule that looks at one square at a time, on the squares next to the
+// changes since the given stamp. They're visited in the same order as a run
+// over the whole board. The rule's own changes add the squares next to them
+// that the run hasn't passed yet.
+fn apply_near_changes(board: &mut Board, rule: fn(&mut Board, Coord) -> Result<()>, since: usize) -> Result<()> {
+    let layout = board.layout;
+    let mut queued = BinaryHeap::new(); // Smallest (row_num, col_num) first
+    let mut visited = None;
+    let mut seen = since;
 
-pub fn solve(board: &mut Board) -> Result<bool> {
-    let solvers = [
-        self::fill_unknown::fill_with_water,
-        self::fill_unknown::fill_with_ships,
-        self::surround_with_water::surround_ships_with_water,
-        self::place_at_ends::place_ships_next_to_ends,
-        self::specify_ships::refine_any_ship_to_specific_ship,
-        self::only_place_it_can_go::find_only_place_for_ships,
-        self::specify_middles::specify_middle,
-        self::surround_middles::surround_middle_with_ships,
-        self::enough_space_for_middle::enough_space_for_middle,
-    ];
-
-    board.print();
     loop {
-        let is_changed = solvers.iter()
-            .map(|solve| {
-                board.clear_dirty();
-                solve(board)?;
+        for change in board.changes_since(seen) {
+            let rows = change.row_num.saturating_sub(1) ..= (change.row_num + 1).min(layout.num_rows - 1);
+            let cols = change.col_num.saturating_sub(1) ..= (change.col_num + 1).min(layout.num_cols - 1);
 
-                if board.dirty() {
-                    board.print()
+            for row_num in rows {
+                for col_num in cols.clone() {
+                    if visited.is_none_or(|visited| (row_num, col_num) > visited) {
+                        queued.push(Reverse((row_num, col_num)));
+                    }
                 }
+            }
+        }
+        seen = board.stamp();
+
+        // A square can be queued more than once
+        let square = loop {
+            match queued.pop() {
+                Some(Reverse(square)) if Some(square) == visited => continue,
+                Some(Reverse(square)) => break square,
+                None                  => return Ok(()),
+            }
+        };
+
+        visited = Some(square);
+        rule(board, layout.coord(square.1, square.0))?;
+    }
+}
+
+// Each rule, along with the name it's reported under, and whether it's cheap
+// enough to run over and over, when probing hypothetical boards
+const RULES: [(&str, Rule, bool); 11] = [
+    ("fill_with_water",                   Rule::Lines(self::fill_unknown::fill_with_water), true),
+    ("fill_with_ships",                   Rule::Lines(self::fill_unknown::fill_with_ships), true),
+    ("surround_ships_with_water",         Rule::Neighbors(self::surround_with_water::surround_ships_with_water), true),
+    ("place_ships_next_to_ends",          Rule::Neighbors(self::place_at_ends::place_ships_next_to_ends), true),
+    ("refine_any_ship_to_specific_ship",  Rule::Neighbors(self::specify_ships::refine_any_ship_to_specific_ship), true),
+    ("find_only_place_for_ships",         Rule::Board(self::only_place_it_can_go::find_only_place_for_ships), false),
+    ("specify_middle",                    Rule::Neighbors(self::specify_middles::specify_middle), true),
+    ("surround_middle_with_ships",        Rule::Neighbors(self::surround_middles::surround_middle_with_ships), true),
+    ("enough_space_for_middle",           Rule::RowAndCol(self::enough_space_for_middle::enough_space_for_middle), true),
+    ("place_fleet_in_segments",           Rule::Board(self::fleet_in_segments::place_fleet_in_segments), false),
+    ("solve_lines",                       Rule::Lines(self::line_solver::solve_lines), false),
+];
+
+// Every rule, in the order they run
+fn all_rules() -> Vec<(&'static str, Rule)> {
+    RULES.iter().map(|&(name, rule, _)| (name, rule)).collect()
+}
+
+// The rules that are cheap enough to run over and over, in the order they run
+fn cheap_rules() -> Vec<(&'static str, Rule)> {
+    RULES.iter()
+        .filter(|&&(_, _, is_cheap)| is_cheap)
+        .map(|&(name, rule, _)| (name, rule))
+        .collect()
+}
+
+// The names the rules are reported under, in the order they run
+pub fn rule_names() -> impl Iterator<Item = &'static str> {
+    RULES.iter().map(|&(name, _, _)| name)
+}
+
+// Look up a rule by name, to run it on its own
+pub(crate) fn rule_from_name(name: &str) -> Result<Rule> {
+    match RULES.iter().find(|&&(rule_name, _, _)| rule_name == name) {
+        Some(&(_, rule, _)) => Ok(rule),
+        None => bail!("Unknown rule: {}. Try one of: {}", name, rule_names().collect::<Vec<_>>().join(", ")),
+    }
+}
+
+// Run one rule, by name, over the whole board
+pub fn apply_rule(board: &mut Board, name: &str) -> Result<()> {
+    rule_from_name(name)?.apply(board)
+}
+
+pub fn solve(board: &mut Board, observer: &mut dyn SolveObserver) -> Result<bool> {
+    let result = solve_with_config(board.clone(), SolveConfig::default(), observer)?;
+    *board = result.board;
+
+    match result.outcome {
+        SolveOutcome::Invalid(error) | SolveOutcome::Contradiction(error) => bail!(error),
+        outcome => Ok(outcome == SolveOutcome::Solved),
+    }
+}
+
+// Like solve, but within the limits in `config`. The board comes back however
+// far the rules got, along with why they stopped: even if the board is invalid,
+// or the rules found that it has no solution. Only errors if the observer does.
+pub fn solve_with_config(mut board: Board, mut config: SolveConfig, observer: &mut dyn SolveObserver)
+    -> Result<SolveResult> {
+
+    if let Err(error) = validate_board(&board) {
+        return Ok(SolveResult { outcome: SolveOutcome::Invalid(error.to_string()), board, passes: 0 });
+    }
+    observer.on_event(&SolveEvent::SolveStarted { board: &board })?;
+
+    let mut passes = 0;
+    let mut checked = CheckedObserver { observer, failed: false };
+    let stopped = match apply_rules(&mut board, &all_rules(), &mut checked, &mut config, &mut passes, None) {
+        Ok(stopped)                      => stopped,
+        Err(error) if checked.failed     => return Err(error),
+        Err(error) => {
+            finish(&board, checked.observer, passes)?;
+            return Ok(SolveResult {
+                outcome: SolveOutcome::Contradiction(error.to_string()),
+                board,
+                passes,
+            });
+        },
+    };
+    let is_solved = finish(&board, observer, passes)?;
+
+    let outcome = match stopped {
+        _ if is_solved => SolveOutcome::Solved,
+        Some(outcome)  => outcome,
+        None           => SolveOutcome::Stalled,
+    };
+
+    Ok(SolveResult { outcome, board, passes })
+}
+
+// Remembers whether the observer failed, to tell its errors apart from the rules'
+struct CheckedObserver<'a> {
+    observer: &'a mut dyn SolveObserver,
+    failed: bool,
+}
+
+impl SolveObserver for CheckedObserver<'_> {
+    fn on_event(&mut self, event: &SolveEvent) -> Result<()> {
+        let result = self.observer.on_event(event);
+        self.failed |= result.is_err();
+
+        result
+    }
+}
+
+// Run the rules until none of them change the board. Tell the observer about
+// each rule that changes something, and about each pass through the rules.
+//
+// `settled` is a stamp from when none of the rules changed the board, if
+// there was such a time. The rules only need to look at what changed since.
+//
+// Returns why the rules were stopped early, if the limits in `config` stopped them.
+fn apply_rules(board: &mut Board, rules: &[(&'static str, Rule)], observer: &mut dyn SolveObserver,
+    config: &mut SolveConfig, passes: &mut usize, settled: Option<usize>) -> Result<Option<SolveOutcome>> {
+
+    // When each rule last started running
+    let mut last_run = vec![settled; rules.len()];
+
+    loop {
+        if config.max_passes.is_some_and(|max_passes| *passes >= max_passes) {
+            return Ok(Some(SolveOutcome::PassLimitReached));
+        }
+
+        *passes += 1;
+        let mut num_changes = 0;
+
+        for (&(name, rule), last_run) in rules.iter().zip(last_run.iter_mut()) {
+            if let Some(outcome) = config.check_limits() {
+                return Ok(Some(outcome));
+            }
 
-                // Compiler needs us to give a type annotation for the return type
-                let result: Result<bool> = Ok(board.dirty());
-                result
-            })
-            .fold_results(false, |acc, curr| acc || curr)?;
+            board.clear_dirty();
+            let since = last_run.replace(board.stamp());
+            rule.apply_since(board, since)?;
+
+            if board.dirty() {
+                num_changes += board.changes().len();
+                observer.on_event(&SolveEvent::RuleApplied {
+                    pass: *passes,
+                    rule: name,
+                    changes: board.changes(),
+                    board,
+                })?;
+            }
+        }
+
+        observer.on_event(&SolveEvent::PassCompleted { pass: *passes, num_changes })?;
+        if let Some(progress) = config.progress.as_mut() {
+            progress(*passes, board);
+        }
 
         // If none of the solvers made a change, it's time to stop
-        if !is_changed {
+        if num_changes == 0 {
+            return Ok(None);
+        }
+    }
+}
+
+fn finish(board: &Board, observer: &mut dyn SolveObserver, passes: usize) -> Result<bool> {
+    let is_solved = board.is_solved();
+    observer.on_event(&SolveEvent::Finished { passes, is_solved, board })?;
+
+    Ok(is_solved)
+}
+
+// Run the rules. When they get stuck, probe each unknown square for contradictions,
+// looking up to `depth` assumptions deep. Keep going until probing finds nothing new.
+pub fn solve_with_probing(board: &mut Board, depth: usize, observer: &mut dyn SolveObserver) -> Result<bool> {
+    validate_board(board)?;
+    observer.on_event(&SolveEvent::SolveStarted { board })?;
+
+    let mut passes = 0;
+    let mut settled = None;
+    loop {
+        apply_rules(board, &all_rules(), observer, &mut SolveConfig::default(), &mut passes, settled)?;
+        if board.is_solved() {
             break;
         }
+
+        settled = Some(board.stamp());
+        let deductions = probe_settled(board, depth, settled)?;
+        if deductions.is_empty() {
+            break;
+        }
+
+        observer.on_event(&SolveEvent::Probed { deductions: &deductions, board })?;
+    }
+
+    finish(board, observer, passes)
+}
+
+// Run the rules, and if they get stuck, search for the rest of the solution
+pub fn solve_with_search(board: &mut Board, observer: &mut dyn SolveObserver) -> Result<bool> {
+    validate_board(board)?;
+    observer.on_event(&SolveEvent::SolveStarted { board })?;
+
+    let mut passes = 0;
+    apply_rules(board, &all_rules(), observer, &mut SolveConfig::default(), &mut passes, None)?;
+
+    if !board.is_solved() {
+        board.clear_dirty();
+        solve_by_search(board)?;
+        observer.on_event(&SolveEvent::RuleApplied {
+            pass: passes,
+            rule: "search",
+            changes: board.changes(),
+            board,
+        })?;
+    }
+
+    finish(board, observer, passes)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::parse_board;
+    use crate::square::*;
+
+    // Each rule that changed the board, and the squares it changed. Sorted,
+    // since surround_ships_with_water doesn't change squares in a fixed order.
+    #[derive(Default)]
+    struct Recorder(Vec<(usize, &'static str, Vec<String>)>);
+
+    impl SolveObserver for Recorder {
+        fn on_event(&mut self, event: &SolveEvent) -> Result<()> {
+            if let SolveEvent::RuleApplied { pass, rule, changes, .. } = event {
+                let mut changes = changes.iter().map(|change| format!("{:?}", change)).collect::<Vec<_>>();
+                changes.sort();
+                self.0.push((*pass, rule, changes));
+            }
+
+            Ok(())
+        }
     }
 
-    Ok(board.is_solved())
+    // Try each unknown square both ways, like probing does. The rules should
+    // find the same things, pass by pass, whether they look at everything or
+    // only around what changed.
+    fn check_revisits(puzzle: &str) -> Result<()> {
+        let mut board = parse_board(puzzle)?;
+        apply_rules(&mut board, &all_rules(), &mut Quiet, &mut SolveConfig::default(), &mut 0, None)?;
+        let settled = board.stamp();
+
+        let layout = board.layout;
+        for coord in layout.all_coordinates().filter(|&coord| board[coord] == Square::Unknown) {
+            for &assumption in [Square::Water, Square::ShipSquare(ShipSquare::Any)].iter() {
+                let run = |settled: Option<usize>| {
+                    let mut hypothetical = board.clone();
+                    let mut recorder = Recorder::default();
+                    let result = hypothetical.set(coord, assumption).and_then(|()|
+                        apply_rules(&mut hypothetical, &all_rules(), &mut recorder, &mut SolveConfig::default(), &mut 0, settled));
+
+                    // After a contradiction, the board depends on the order it was found in
+                    (recorder.0, result.ok().map(|_| hypothetical.to_strings()))
+                };
+
+                assert!(run(Some(settled)) == run(None), "Assuming {:?} at {:?}", assumption, coord);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_only_revisits_what_changed_but_finds_everything() -> Result<()> {
+        for puzzle in [
+            include_str!("../puzzles/generated-6x6-seed1.txt"),
+            include_str!("../puzzles/generated-8x8-seed9.txt"),
+            include_str!("../puzzles/generated-10x10-seed2.txt"),
+            include_str!("../puzzles/generated-10x10-seed8.txt"),
+            include_str!("../puzzles/generated-15x15-seed10.txt"),
+            include_str!("../puzzles/puzzle4.txt"),
+        ].iter() {
+            check_revisits(puzzle)?;
+        }
+
+        Ok(())
+    }
+
+    // Water spreads east, one square at a time
+    fn spread_east(board: &mut Board, coord: Coord) -> Result<()> {
+        let west = coord.neighbor(crate::neighbor::Neighbor::W);
+        if board[coord] == Square::Unknown && west.is_some_and(|west| board[west] == Square::Water) {
+            board.set(coord, Square::Water)?;
+        }
+
+        Ok(())
+    }
+
+    // Water fills any col that has some
+    fn fill_col(board: &mut Board, coord: Coord) -> Result<()> {
+        if board[coord] == Square::Unknown && coord.col().coords().any(|other| board[other] == Square::Water) {
+            board.set(coord, Square::Water)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_revisits_squares_next_to_the_rules_own_changes() -> Result<()> {
+        let mut board = Board::new(&[
+            "  0000000000",
+            "0|          ",
+        ])?;
+        let layout = board.layout;
+        let since = board.stamp();
+        board.set(layout.coord(0, 0), Square::Water)?;
+
+        Rule::Neighbors(spread_east).apply_since(&mut board, Some(since))?;
+        assert_eq!(board.to_strings()[1], "0|~~~~~~~~~~");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_revisits_the_col_of_a_change() -> Result<()> {
+        let mut board = Board::new(&[
+            "  000",
+            "0|   ",
+            "0|   ",
+            "0|   ",
+        ])?;
+        let layout = board.layout;
+        let since = board.stamp();
+        board.set(layout.coord(1, 0), Square::Water)?;
+
+        Rule::RowAndCol(fill_col).apply_since(&mut board, Some(since))?;
+        assert_eq!(board.to_strings()[1 ..], ["0| ~ ", "0| ~ ", "0| ~ "]);
+
+        Ok(())
+    }
 }
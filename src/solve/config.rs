@@ -0,0 +1,284 @@
+/////////////////////////////////////////////////////////////////////
+//
+// Limits on how much work the solver does.
+//
+// The limits are checked between rule invocations, so a single slow rule
+// can run past the deadline, but the solver stops right after it.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::board::*;
+
+// Lets another thread ask a solve to stop. Clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+// Called after each pass through the rules, with the pass number and the board so far
+pub type ProgressCallback = Box<dyn FnMut(usize, &Board)>;
+
+#[derive(Default)]
+pub struct SolveConfig {
+    pub max_passes: Option<usize>,
+    pub deadline: Option<Instant>,
+    pub cancellation: Option<CancellationToken>,
+    pub progress: Option<ProgressCallback>,
+}
+
+impl SolveConfig {
+    // Has a limit been hit, other than the number of passes?
+    pub(super) fn check_limits(&self) -> Option<SolveOutcome> {
+        if self.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled) {
+            Some(SolveOutcome::Cancelled)
+        }
+        else if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            Some(SolveOutcome::TimedOut)
+        }
+        else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SolveOutcome {
+    Solved,
+
+    // The rules stopped making progress
+    Stalled,
+
+    Cancelled,
+    TimedOut,
+    PassLimitReached,
+
+    // The board can't be solved as given, for example because the fleet
+    // doesn't match the counts. The rules never ran.
+    Invalid(String),
+
+    // The rules found that the board has no solution, partway through
+    Contradiction(String),
+}
+
+// How a solve ended. The board is as far as the solver got, solved or not.
+#[derive(Clone)]
+pub struct SolveResult {
+    pub outcome: SolveOutcome,
+    pub board: Board,
+    pub passes: usize,
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::error::*;
+    use crate::solve::{solve_with_config, Quiet, SolveEvent, SolveObserver};
+
+    // After the first pass through the rules, 23 squares are still unknown.
+    // It takes 4 passes to solve, the last of which changes nothing.
+    fn puzzle() -> Result<Board> {
+        Board::new(&[
+            "ships: 4sq x 1, 3sq x 1, ",
+            "       2sq x 2, 1sq x 3.",
+            "  1420213",
+            "2|       ",
+            "2|   <   ",
+            "1|       ",
+            "1|       ",
+            "3|       ",
+            "1|       ",
+            "3|       ",
+        ])
+    }
+
+    // Remembers how the solve finished: after how many passes, and whether it was solved
+    #[derive(Default)]
+    struct Finish(Option<(usize, bool)>);
+
+    impl SolveObserver for Finish {
+        fn on_event(&mut self, event: &SolveEvent) -> Result<()> {
+            if let SolveEvent::Finished { passes, is_solved, .. } = event {
+                self.0 = Some((*passes, *is_solved));
+            }
+
+            Ok(())
+        }
+    }
+
+    fn unknown_squares(board: &Board) -> usize {
+        board.layout.all_coordinates()
+            .filter(|&coord| board[coord] == crate::square::Square::Unknown)
+            .count()
+    }
+
+    #[test]
+    fn it_solves_without_limits() -> Result<()> {
+        let result = solve_with_config(puzzle()?, SolveConfig::default(), &mut Quiet)?;
+
+        assert_eq!(result.outcome, SolveOutcome::Solved);
+        assert!(result.board.is_solved());
+        assert_eq!(result.passes, 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_stalls_when_rules_are_stuck() -> Result<()> {
+        let board = Board::new(&[
+            "ships: 1sq x 2.",
+            "  101",
+            "1|   ",
+            "0|   ",
+            "1|   ",
+        ])?;
+        let result = solve_with_config(board, SolveConfig::default(), &mut Quiet)?;
+
+        assert_eq!(result.outcome, SolveOutcome::Stalled);
+        assert_eq!(unknown_squares(&result.board), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_stops_after_max_passes() -> Result<()> {
+        let config = SolveConfig { max_passes: Some(1), ..Default::default() };
+        let result = solve_with_config(puzzle()?, config, &mut Quiet)?;
+
+        assert_eq!(result.outcome, SolveOutcome::PassLimitReached);
+        assert_eq!(result.passes, 1);
+
+        // The first pass got part of the way
+        assert_eq!(unknown_squares(&result.board), 23);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_stops_when_cancelled() -> Result<()> {
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+
+        let config = SolveConfig { cancellation: Some(cancellation), ..Default::default() };
+        let result = solve_with_config(puzzle()?, config, &mut Quiet)?;
+
+        assert_eq!(result.outcome, SolveOutcome::Cancelled);
+        assert_eq!(unknown_squares(&result.board), 48);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_stops_at_the_deadline() -> Result<()> {
+        let config = SolveConfig {
+            deadline: Some(Instant::now() - Duration::from_secs(1)),
+            ..Default::default()
+        };
+        let result = solve_with_config(puzzle()?, config, &mut Quiet)?;
+
+        assert_eq!(result.outcome, SolveOutcome::TimedOut);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_returns_an_invalid_board() -> Result<()> {
+        let board = Board::new(&[
+            "ships: 1sq x 1.",
+            "  11",
+            "1|  ",
+            "1|  ",
+        ])?;
+        let result = solve_with_config(board, SolveConfig::default(), &mut Quiet)?;
+
+        assert!(matches!(result.outcome, SolveOutcome::Invalid(_)));
+        assert_eq!(unknown_squares(&result.board), 4);
+        assert_eq!(result.passes, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_returns_the_board_so_far_after_a_contradiction() -> Result<()> {
+        let board = Board::new(&[
+            "ships: 2sq x 1.",
+            "  2000",
+            "0|    ",
+            "1|~   ",
+            "1|    ",
+        ])?;
+        let result = solve_with_config(board, SolveConfig::default(), &mut Quiet)?;
+
+        assert_eq!(result.outcome, SolveOutcome::Contradiction(
+            "Row: 1 needs 1 more ship squares, but only has 0 unknown squares".to_string()));
+
+        // Row 0 was filled with water before the rules ran into it
+        assert_eq!(result.board.to_strings()[2], "0|~~~~");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_finishes_after_a_contradiction() -> Result<()> {
+        let board = Board::new(&[
+            "ships: 2sq x 1.",
+            "  2000",
+            "0|    ",
+            "1|~   ",
+            "1|    ",
+        ])?;
+        let mut finish = Finish::default();
+        let result = solve_with_config(board, SolveConfig::default(), &mut finish)?;
+
+        assert!(matches!(result.outcome, SolveOutcome::Contradiction(_)));
+        assert_eq!(finish.0, Some((result.passes, false)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_reports_progress() -> Result<()> {
+        let cancellation = CancellationToken::new();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+
+        // Cancel from the progress callback, once the first pass is done
+        let progress = {
+            let cancellation = cancellation.clone();
+            let seen = seen.clone();
+            move |pass: usize, board: &Board| {
+                seen.borrow_mut().push((pass, unknown_squares(board)));
+                cancellation.cancel();
+            }
+        };
+
+        let config = SolveConfig {
+            cancellation: Some(cancellation),
+            progress: Some(Box::new(progress)),
+            ..Default::default()
+        };
+        let result = solve_with_config(puzzle()?, config, &mut Quiet)?;
+
+        assert_eq!(result.outcome, SolveOutcome::Cancelled);
+        assert_eq!(*seen.borrow(), vec![(1, 23)]);
+        assert_eq!(unknown_squares(&result.board), 23);
+
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use crate::board::*;
 use crate::error::*;
+use crate::layout::*;
 use crate::neighbor::*;
 use crate::square::*;
 
@@ -7,49 +8,45 @@ use crate::square::*;
 
 // Determine if an AnyMiddle should be vertical or horizontal by looking at the
 // number of ship squares remaining. 
-pub fn enough_space_for_middle(board: &mut Board) -> Result<()> {
-    let layout = board.layout;
-
-    for coord in layout.all_coordinates() {
-        if board[coord] != Square::ShipSquare(ShipSquare::AnyMiddle) {
-            continue;
-        }
+pub fn enough_space_for_middle(board: &mut Board, coord: Coord) -> Result<()> {
+    if board[coord] != Square::ShipSquare(ShipSquare::AnyMiddle) {
+        return Ok(());
+    }
 
-        // Skip squares that are adjacent to board edges, or adjacent to a known square.
-        // Other rules will fill those squares in.
-        let neighbors = [Neighbor::N, Neighbor::E, Neighbor::S, Neighbor::W];
-        let neighbors_ok = neighbors.iter().all(|neighbor| {
-            match coord.neighbor(*neighbor) {
-                Some(neighbor_coord) => board[neighbor_coord] == Square::Unknown,
-                None => false,
-            }
-        });
-        if !neighbors_ok {
-            continue;
+    // Skip squares that are adjacent to board edges, or adjacent to a known square.
+    // Other rules will fill those squares in.
+    let neighbors = [Neighbor::N, Neighbor::E, Neighbor::S, Neighbor::W];
+    let neighbors_ok = neighbors.iter().all(|neighbor| {
+        match coord.neighbor(*neighbor) {
+            Some(neighbor_coord) => board[neighbor_coord] == Square::Unknown,
+            None => false,
         }
+    });
+    if !neighbors_ok {
+        return Ok(());
+    }
 
-        let has_space_in_row = board.ship_squares_remaining(coord.row()) >= 2;
-        let has_space_in_col = board.ship_squares_remaining(coord.col()) >= 2;
-        let ship_type_opt = match (has_space_in_row, has_space_in_col) {
-            // The row contains enough space for a ship start and ship end,
-            // but the col does not. This must be a horizontal ship.
-            (true,  false) => Some(ShipSquare::HorizontalMiddle),
-
-            // Must be a vertical ship
-            (false, true)  => Some(ShipSquare::VerticalMiddle),
+    let has_space_in_row = board.ship_squares_remaining(coord.row()) >= 2;
+    let has_space_in_col = board.ship_squares_remaining(coord.col()) >= 2;
+    let ship_type_opt = match (has_space_in_row, has_space_in_col) {
+        // The row contains enough space for a ship start and ship end,
+        // but the col does not. This must be a horizontal ship.
+        (true,  false) => Some(ShipSquare::HorizontalMiddle),
 
-            // Ship could be either vertical or horizontal. Do nothing.
-            (true,  true)  => None,
+        // Must be a vertical ship
+        (false, true)  => Some(ShipSquare::VerticalMiddle),
 
-            // Error: Neither row nor col has enough space for a ship here
-            (false, false) => {
-                bail!("Ship middle has no space to become vertical or horizontal. {:?}", coord)
-            }
-        };
+        // Ship could be either vertical or horizontal. Do nothing.
+        (true,  true)  => None,
 
-        if let Some(ship_type) = ship_type_opt {
-            board.set(coord, Square::ShipSquare(ship_type))?;
+        // Error: Neither row nor col has enough space for a ship here
+        (false, false) => {
+            bail!("Ship middle has no space to become vertical or horizontal. {:?}", coord)
         }
+    };
+
+    if let Some(ship_type) = ship_type_opt {
+        board.set(coord, Square::ShipSquare(ship_type))?;
     }
 
     Ok(())
@@ -58,12 +55,13 @@ pub fn enough_space_for_middle(board: &mut Board) -> Result<()> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::solve::Rule;
 
     fn do_test(before: Vec<&str>, after: Vec<&str>) -> Result<()> {
         let mut board = Board::new(&before)?;
         let expected = after.iter().map(|x| x.to_string()).collect::<Vec<_>>();
 
-        enough_space_for_middle(&mut board)?;
+        Rule::RowAndCol(enough_space_for_middle).apply(&mut board)?;
         assert_eq!(board.to_strings(), expected);     
 
         Ok(())   
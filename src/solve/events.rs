@@ -0,0 +1,295 @@
+/////////////////////////////////////////////////////////////////////
+//
+// Reporting on the solver's progress.
+//
+// The solver doesn't print anything itself. It tells a SolveObserver what
+// it's doing, and the observer decides what, if anything, to do with that.
+
+use std::io::{self, Write};
+
+use crate::board::*;
+use crate::error::*;
+use crate::square::*;
+
+use super::Deduction;
+
+pub enum SolveEvent<'a> {
+    SolveStarted { board: &'a Board },
+
+    // A rule changed some squares
+    RuleApplied { pass: usize, rule: &'static str, changes: &'a [SquareChange], board: &'a Board },
+
+    // Probing filled in squares the rules couldn't
+    Probed { deductions: &'a [Deduction], board: &'a Board },
+
+    // Every rule has been run once more
+    PassCompleted { pass: usize, num_changes: usize },
+
+    Finished { passes: usize, is_solved: bool, board: &'a Board },
+}
+
+pub trait SolveObserver {
+    fn on_event(&mut self, event: &SolveEvent) -> Result<()>;
+}
+
+// Pick one of the stock observers by name, writing boards to stdout in the given style
+pub fn observer_from_name(name: &str, style: TextStyle) -> Result<Box<dyn SolveObserver>> {
+    let observer: Box<dyn SolveObserver> = match name {
+        "quiet"   => Box::new(Quiet),
+        "summary" => Box::new(Summary::new(io::stdout())),
+        "verbose" => Box::new(VerboseText::new(io::stdout()).with_style(style)),
+        "json"    => Box::new(JsonLines::new(io::stdout()).with_style(style)),
+        _         => bail!("Unknown event output: {}. Expected quiet, summary, verbose, or json", name),
+    };
+
+    Ok(observer)
+}
+
+/////////////////////////////////////////////////////////////////////
+//
+// Stock observers
+
+pub struct Quiet;
+
+impl SolveObserver for Quiet {
+    fn on_event(&mut self, _event: &SolveEvent) -> Result<()> {
+        Ok(())
+    }
+}
+
+// One line at the end
+pub struct Summary<W: Write> {
+    writer: W,
+    rules_applied: usize,
+    squares_changed: usize,
+}
+
+impl<W: Write> Summary<W> {
+    pub fn new(writer: W) -> Self {
+        Summary { writer, rules_applied: 0, squares_changed: 0 }
+    }
+}
+
+impl<W: Write> SolveObserver for Summary<W> {
+    fn on_event(&mut self, event: &SolveEvent) -> Result<()> {
+        match event {
+            SolveEvent::RuleApplied { changes, .. } => {
+                self.rules_applied += 1;
+                self.squares_changed += changes.len();
+            },
+            SolveEvent::Probed { deductions, .. } => self.squares_changed += deductions.len(),
+            SolveEvent::Finished { passes, is_solved, .. } => {
+                writeln!(self.writer, "{} after {} passes. Rules changed the board {} times, making {} changes to squares.",
+                    if *is_solved { "Solved" } else { "Stuck" },
+                    passes, self.rules_applied, self.squares_changed)
+                    .chain_err(|| "Unable to write summary")?;
+            },
+            _ => {},
+        }
+
+        Ok(())
+    }
+}
+
+// The board at the start, and again after every rule that changes it
+pub struct VerboseText<W: Write> {
+    writer: W,
+    style: TextStyle,
+}
+
+impl<W: Write> VerboseText<W> {
+    pub fn new(writer: W) -> Self {
+        VerboseText { writer, style: TextStyle::Unicode }
+    }
+
+    pub fn with_style(self, style: TextStyle) -> Self {
+        VerboseText { style, ..self }
+    }
+
+    fn write_board(&mut self, board: &Board) -> Result<()> {
+        for line in board.to_strings_with_style(self.style) {
+            writeln!(self.writer, "{}", line).chain_err(|| "Unable to write board")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: Write> SolveObserver for VerboseText<W> {
+    fn on_event(&mut self, event: &SolveEvent) -> Result<()> {
+        match event {
+            SolveEvent::SolveStarted { board } => self.write_board(board)?,
+            SolveEvent::RuleApplied { rule, changes, board, .. } => {
+                writeln!(self.writer, "{} changed {} squares:", rule, changes.len())
+                    .chain_err(|| "Unable to write rule")?;
+                self.write_board(board)?;
+            },
+            SolveEvent::Probed { deductions, board } => {
+                for deduction in deductions.iter() {
+                    writeln!(self.writer, "{}", deduction).chain_err(|| "Unable to write deduction")?;
+                }
+                self.write_board(board)?;
+            },
+            _ => {},
+        }
+
+        Ok(())
+    }
+}
+
+// One JSON object per line, for other programs to read
+pub struct JsonLines<W: Write> {
+    writer: W,
+    style: TextStyle,
+}
+
+impl<W: Write> JsonLines<W> {
+    pub fn new(writer: W) -> Self {
+        JsonLines { writer, style: TextStyle::Unicode }
+    }
+
+    pub fn with_style(self, style: TextStyle) -> Self {
+        JsonLines { style, ..self }
+    }
+}
+
+impl<W: Write> SolveObserver for JsonLines<W> {
+    fn on_event(&mut self, event: &SolveEvent) -> Result<()> {
+        let line = match event {
+            SolveEvent::SolveStarted { board } =>
+                format!(r#"{{"event":"solve_started","board":{}}}"#, json_board(board, self.style)),
+            SolveEvent::RuleApplied { pass, rule, changes, .. } => {
+                let changes = changes.iter()
+                    .map(|change| format!(r#"{{"row":{},"col":{},"before":{},"after":{}}}"#,
+                        change.row_num, change.col_num,
+                        json_string(&change.before.to_char(self.style).to_string()),
+                        json_string(&change.after.to_char(self.style).to_string())))
+                    .collect::<Vec<_>>();
+                format!(r#"{{"event":"rule_applied","pass":{},"rule":{},"changes":[{}]}}"#,
+                    pass, json_string(rule), changes.join(","))
+            },
+            SolveEvent::Probed { deductions, .. } => {
+                let deductions = deductions.iter()
+                    .map(|deduction| format!(r#"{{"row":{},"col":{},"value":{},"reason":{}}}"#,
+                        deduction.row_num, deduction.col_num,
+                        json_string(&deduction.value.to_char(self.style).to_string()),
+                        json_string(&deduction.to_string())))
+                    .collect::<Vec<_>>();
+                format!(r#"{{"event":"probed","deductions":[{}]}}"#, deductions.join(","))
+            },
+            SolveEvent::PassCompleted { pass, num_changes } =>
+                format!(r#"{{"event":"pass_completed","pass":{},"num_changes":{}}}"#, pass, num_changes),
+            SolveEvent::Finished { passes, is_solved, board } =>
+                format!(r#"{{"event":"finished","passes":{},"solved":{},"board":{}}}"#,
+                    passes, is_solved, json_board(board, self.style)),
+        };
+
+        writeln!(self.writer, "{}", line).chain_err(|| "Unable to write event")
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut out = "\"".to_string();
+    for ch in text.chars() {
+        match ch {
+            '"'  => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            ch if (ch as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch   => out.push(ch),
+        }
+    }
+    out.push('"');
+
+    out
+}
+
+fn json_board(board: &Board, style: TextStyle) -> String {
+    let lines = board.to_strings_with_style(style).iter()
+        .map(|line| json_string(line))
+        .collect::<Vec<_>>();
+
+    format!("[{}]", lines.join(","))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solve::solve;
+
+    fn puzzle() -> Result<Board> {
+        Board::new(&[
+            "ships: 2sq x 1.",
+            "  01",
+            "1|  ",
+            "0| v",
+        ])
+    }
+
+    fn solve_with(observer: &mut dyn SolveObserver) -> Result<()> {
+        let mut board = puzzle()?;
+        assert!(solve(&mut board, observer)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_writes_summary() -> Result<()> {
+        let mut out = Vec::new();
+        solve_with(&mut Summary::new(&mut out))?;
+
+        assert_eq!(String::from_utf8(out).unwrap(),
+            "Solved after 2 passes. Rules changed the board 3 times, making 4 changes to squares.\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_writes_boards_after_rules() -> Result<()> {
+        let mut out = Vec::new();
+        solve_with(&mut VerboseText::new(&mut out))?;
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[.. 4], [
+            "ships: 2sq x 1.",
+            "  01",
+            "1|  ",
+            "0| v",
+        ]);
+        assert_eq!(lines[4], "fill_with_water changed 2 squares:");
+        assert_eq!(lines[lines.len() - 3 ..], [
+            "  00",
+            "0|~^",
+            "0|~v",
+        ]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_writes_json_lines() -> Result<()> {
+        let mut out = Vec::new();
+        solve_with(&mut JsonLines::new(&mut out))?;
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], r#"{"event":"solve_started","board":["ships: 2sq x 1.","  01","1|  ","0| v"]}"#);
+        assert_eq!(lines[1], r#"{"event":"rule_applied","pass":1,"rule":"fill_with_water","changes":[{"row":1,"col":0,"before":" ","after":"~"},{"row":0,"col":0,"before":" ","after":"~"}]}"#);
+        assert!(lines.contains(&r#"{"event":"pass_completed","pass":2,"num_changes":0}"#));
+        assert_eq!(lines[lines.len() - 1], r#"{"event":"finished","passes":2,"solved":true,"board":["ships: 2sq x 0.","  00","0|~^","0|~v"]}"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_escapes_json_strings() {
+        assert_eq!(json_string(r#"a "quoted" \ line"#), r#""a \"quoted\" \\ line""#);
+        assert_eq!(json_string("tab\there"), r#""tab\u0009here""#);
+    }
+
+    #[test]
+    fn it_picks_observers_by_name() {
+        assert!(observer_from_name("json", TextStyle::Ascii).is_ok());
+        assert!(observer_from_name("loud", TextStyle::Unicode).is_err());
+    }
+}
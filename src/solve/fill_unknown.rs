@@ -4,46 +4,48 @@
 
 use crate::board::*;
 use crate::error::*;
+use crate::layout::*;
 use crate::square::*;
 
 
-pub fn fill_with_water(board: &mut Board) -> Result<()>  {
-    let layout = board.layout;
-    for row_or_col in layout.rows_and_cols() {
-        if board.ship_squares_remaining(row_or_col) == 0 {
-            board.replace_unknown(row_or_col, Square::Water)?
-        }
+pub fn fill_with_water(board: &mut Board, row_or_col: RowOrCol) -> Result<()>  {
+    if board.ship_squares_remaining(row_or_col) == 0 {
+        board.replace_unknown(row_or_col, Square::Water)?
     }
     Ok(())
 }
 
 // If number of Unknown squares on an axis == number of ships unaccounted for,
 // fill the blank spots with ships
-pub fn fill_with_ships(board: &mut Board) -> Result<()> {
-    let layout = board.layout;
-    for row_or_col in layout.rows_and_cols() {
-        let num_unknown = row_or_col.coords()
-            .filter(|coord| board[*coord] == Square::Unknown)
-            .count();
-
-        if num_unknown == board.ship_squares_remaining(row_or_col) {
-            board.replace_unknown(row_or_col, Square::ShipSquare(ShipSquare::Any))?
-        }
+pub fn fill_with_ships(board: &mut Board, row_or_col: RowOrCol) -> Result<()> {
+    let num_unknown = row_or_col.coords()
+        .filter(|coord| board[*coord] == Square::Unknown)
+        .count();
+    let ship_squares_remaining = board.ship_squares_remaining(row_or_col);
+
+    ensure!(num_unknown >= ship_squares_remaining,
+        "{} needs {} more ship squares, but only has {} unknown squares",
+        row_or_col, ship_squares_remaining, num_unknown);
+
+    if num_unknown == ship_squares_remaining {
+        board.replace_unknown(row_or_col, Square::ShipSquare(ShipSquare::Any))?
     }
 
     Ok(())
 }
 
+#[cfg(test)] use super::Rule;
+
 #[test]
 fn it_fills_with_ships() -> Result<()> {
-    let mut board = Board::new(&vec![
+    let mut board = Board::new(&[
         "  0110",
         "2|    ", // don't change, unknown != ships remaining
         "2|~  ~", // do change, unknown == ships remaining
         "0|~~~~", // don't barf
     ])?;
 
-    let _ = fill_with_ships(&mut board)?;
+    Rule::Lines(fill_with_ships).apply(&mut board)?;
 
     let expected = vec![
         "  0000".to_string(),
@@ -62,13 +64,13 @@ mod test {
 
 	#[test]
 	fn it_fills_with_water() -> Result<()> {
-	    let mut board = Board::new(&vec![
+	    let mut board = Board::new(&[
 	        "  0011",
 	        "0|~*  ",
 	        "2|~*  ",
 	    ])?;
 
-	    let _ = fill_with_water(&mut board)?;
+	    Rule::Lines(fill_with_water).apply(&mut board)?;
 
 	    let result = board.to_strings();
 	    let expected = vec![
@@ -0,0 +1,482 @@
+/////////////////////////////////////////////////////////////////////
+//
+// Reason about the whole remaining fleet at once.
+//
+// find_only_place_for_ships looks at one size of ship at a time. This rule
+// looks at every remaining ship of size 2 or more together. It finds the open
+// segments in each row and col (runs of squares that aren't water), and the
+// places in those segments where each ship could go. Then it tries every way
+// of assigning the fleet to those places, so that no two ships overlap or touch,
+// and no row/col gets more ship squares than it has left.
+//
+// A square that's a ship in every consistent assignment must be a ship.
+
+use std::collections::HashSet;
+
+use crate::board::*;
+use crate::error::*;
+use crate::layout::*;
+use crate::ship::*;
+use crate::square::*;
+
+use super::only_place_it_can_go::is_possible_placement;
+
+// Give up (without making any changes) after exploring this many partial assignments.
+// The rule runs on every pass, so it has to stay cheap. On the reference puzzles
+// the search never needs more than a couple of thousand. The ones that hit the
+// limit are boards with little filled in yet, where the fleet fits so many ways
+// that no square would be forced anyway. At 20,000 a 15x15 board takes ~10ms.
+const MAX_NODES: usize = 20_000;
+
+pub fn place_fleet_in_segments(board: &mut Board) -> Result<()> {
+    let fleet = Fleet::new(board);
+
+    if let Some(forced) = fleet.squares_in_every_assignment() {
+        let layout = board.layout;
+        for idx in forced {
+            let coord = layout.coord(idx % layout.num_cols, idx / layout.num_cols);
+            if board[coord] == Square::Unknown {
+                board.set(coord, Square::ShipSquare(ShipSquare::Any))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// A maximal run of squares in a row or col that aren't water
+#[derive(Debug, PartialEq, Eq)]
+struct Segment {
+    axis: Axis,   // Row if the segment is part of a row, Col if it's part of a col
+    index: usize, // which row/col
+    start: usize, // index of the first square within the row/col
+    len: usize,
+}
+
+impl Segment {
+    // The coord of the nth square in the segment
+    fn coord<'a>(&self, layout: &'a Layout, offset: usize) -> Coord<'a> {
+        match self.axis {
+            Axis::Row => layout.coord(self.start + offset, self.index),
+            Axis::Col => layout.coord(self.index, self.start + offset),
+        }
+    }
+}
+
+fn open_segments(board: &Board) -> Vec<Segment> {
+    let mut segments = Vec::new();
+
+    for row_or_col in board.layout.rows_and_cols() {
+        let mut start = None;
+        let coords = row_or_col.coords().collect::<Vec<_>>();
+
+        // Walk one square past the end, so the last segment gets closed
+        for position in 0 ..= coords.len() {
+            let is_open = position < coords.len() && board[coords[position]] != Square::Water;
+
+            match (start, is_open) {
+                (None, true) => start = Some(position),
+                (Some(segment_start), false) => {
+                    segments.push(Segment {
+                        axis: row_or_col.axis,
+                        index: row_or_col.index,
+                        start: segment_start,
+                        len: position - segment_start,
+                    });
+                    start = None;
+                },
+                _ => {},
+            }
+        }
+    }
+
+    segments
+}
+
+// A place a ship could go, with squares flattened to indexes into the grid
+struct Candidate {
+    size: usize,
+    squares: Vec<usize>,
+    blocks: Vec<usize>,       // squares + the water around them
+
+    // Each line with squares that are currently unknown, and how many of them are in it
+    lines: Vec<(usize, usize)>,
+}
+
+struct Fleet {
+    num_rows: usize,
+    num_cols: usize,
+
+    // Ships to place, largest first
+    ships: Vec<usize>,
+    candidates: Vec<Candidate>,
+
+    // Ship squares on the board that can only belong to a ship of size 2 or more
+    must_cover: Vec<usize>,
+
+    // Ship squares still allowed in each line. Rows first, then cols.
+    needed: Vec<usize>,
+}
+
+struct State {
+    blocked: Vec<usize>, // How many chosen candidates block each square
+    needed: Vec<usize>,
+    chosen: Vec<usize>,
+    nodes: usize,
+
+    // Squares that are ships in every assignment so far. None until we find the first one.
+    intersection: Option<Vec<bool>>,
+}
+
+impl Fleet {
+    fn new(board: &Board) -> Self {
+        let layout = board.layout;
+        let num_cols = layout.num_cols;
+        let to_idx = |coord: Coord| coord.row_num * num_cols + coord.col_num;
+
+        let mut ships = board.remaining_expected_ships()
+            .filter(|expected_ship| expected_ship.size >= 2)
+            .flat_map(|expected_ship| {
+                let count = board.num_remaining_ships_to_find(expected_ship);
+                std::iter::repeat_n(expected_ship.size, count)
+            })
+            .collect::<Vec<_>>();
+        ships.sort_by(|a, b| b.cmp(a));
+
+        let sizes = ships.iter().cloned().collect::<HashSet<_>>();
+
+        let mut candidates = Vec::new();
+        for segment in open_segments(board) {
+            let incrementing_axis = segment.axis.cross_axis();
+
+            for &size in sizes.iter() {
+                for offset in 0 .. (segment.len + 1).saturating_sub(size) {
+                    let origin = segment.coord(&layout, offset);
+                    let ship = Ship::new(origin, incrementing_axis, size);
+
+                    if is_possible_placement(board, ship) {
+                        candidates.push(Candidate::new(board, ship));
+                    }
+                }
+            }
+        }
+
+        // Squares of ships that are already complete don't need covering
+        let found_squares = board.fleet()
+            .flat_map(|(expected_ship, _)| {
+                layout.possible_heads_for_ship(expected_ship)
+                    .map(move |ship_head| ship_head.to_ship(expected_ship))
+            })
+            .filter(|&ship| board.ship_is_found(ship))
+            .flat_map(|ship| ship.coords().unwrap().map(to_idx).collect::<Vec<_>>())
+            .collect::<HashSet<_>>();
+
+        // Any square could be a dot, so only ends and middles must be covered
+        let must_cover = layout.all_coordinates()
+            .filter(|&coord| match board[coord] {
+                Square::ShipSquare(ShipSquare::Any) |
+                Square::ShipSquare(ShipSquare::Dot) => false,
+                square                              => square.is_ship(),
+            })
+            .map(to_idx)
+            .filter(|idx| !found_squares.contains(idx))
+            .collect();
+
+        let needed = layout.rows_and_cols()
+            .map(|row_or_col| board.ship_squares_remaining(row_or_col))
+            .collect();
+
+        Fleet {
+            num_rows: layout.num_rows,
+            num_cols,
+            ships,
+            candidates,
+            must_cover,
+            needed,
+        }
+    }
+
+    // Returns None if we have nothing to say: there's nothing to place, we
+    // ran out of time, or no square is a ship in every assignment.
+    fn squares_in_every_assignment(&self) -> Option<Vec<usize>> {
+        if self.ships.is_empty() {
+            return None;
+        }
+
+        let mut state = State {
+            blocked: vec![0; self.num_rows * self.num_cols],
+            needed: self.needed.clone(),
+            chosen: Vec::new(),
+            nodes: 0,
+            intersection: None,
+        };
+
+        if !self.assign(0, &mut state) {
+            return None;
+        }
+
+        let intersection = state.intersection?;
+        let forced = intersection.iter()
+            .enumerate()
+            .filter(|(_, &is_ship)| is_ship)
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+
+        if forced.is_empty() { None } else { Some(forced) }
+    }
+
+    // Assign a place to ships[ship_idx ..]. Returns false if the search should stop.
+    fn assign(&self, ship_idx: usize, state: &mut State) -> bool {
+        state.nodes += 1;
+        if state.nodes > MAX_NODES {
+            return false;
+        }
+
+        if ship_idx == self.ships.len() {
+            self.record_assignment(state);
+
+            // Once no square is common to every assignment, there's no point continuing
+            return state.intersection.as_ref()
+                .is_none_or(|intersection| intersection.iter().any(|&is_ship| is_ship));
+        }
+
+        let size = self.ships[ship_idx];
+
+        // Ships of the same size are interchangeable. Only try them in increasing
+        // candidate order, so each set of places is only tried once.
+        let first_candidate = match state.chosen.last() {
+            Some(&prev) if ship_idx > 0 && self.ships[ship_idx - 1] == size => prev + 1,
+            _ => 0,
+        };
+
+        for candidate_idx in first_candidate .. self.candidates.len() {
+            let candidate = &self.candidates[candidate_idx];
+            if candidate.size != size || !self.fits(candidate, state) {
+                continue;
+            }
+
+            self.push(candidate_idx, state);
+            let keep_going = self.assign(ship_idx + 1, state);
+            self.pop(state);
+
+            if !keep_going {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn fits(&self, candidate: &Candidate, state: &State) -> bool {
+        candidate.squares.iter().all(|&idx| state.blocked[idx] == 0)
+            && candidate.lines.iter().all(|&(line, count)| state.needed[line] >= count)
+    }
+
+    fn push(&self, candidate_idx: usize, state: &mut State) {
+        let candidate = &self.candidates[candidate_idx];
+
+        for &(line, count) in candidate.lines.iter() {
+            state.needed[line] -= count;
+        }
+        for &idx in candidate.blocks.iter() {
+            state.blocked[idx] += 1;
+        }
+
+        state.chosen.push(candidate_idx);
+    }
+
+    fn pop(&self, state: &mut State) {
+        let candidate_idx = state.chosen.pop().unwrap();
+        let candidate = &self.candidates[candidate_idx];
+
+        for &(line, count) in candidate.lines.iter() {
+            state.needed[line] += count;
+        }
+        for &idx in candidate.blocks.iter() {
+            state.blocked[idx] -= 1;
+        }
+    }
+
+    fn record_assignment(&self, state: &mut State) {
+        let mut is_ship = vec![false; self.num_rows * self.num_cols];
+        for &candidate_idx in state.chosen.iter() {
+            for &idx in self.candidates[candidate_idx].squares.iter() {
+                is_ship[idx] = true;
+            }
+        }
+
+        // Ends and middles must belong to one of these ships
+        if !self.must_cover.iter().all(|&idx| is_ship[idx]) {
+            return;
+        }
+
+        state.intersection = Some(match state.intersection.take() {
+            None               => is_ship,
+            Some(intersection) => intersection.iter()
+                .zip(is_ship.iter())
+                .map(|(&a, &b)| a && b)
+                .collect(),
+        });
+    }
+}
+
+impl Candidate {
+    fn new(board: &Board, ship: Ship) -> Self {
+        let layout = board.layout;
+        let num_cols = layout.num_cols;
+        let to_idx = |coord: Coord| coord.row_num * num_cols + coord.col_num;
+
+        let coords = ship.coords().unwrap().collect::<Vec<_>>();
+
+        // Rows first, then cols, like Fleet::needed
+        let mut lines = Vec::<(usize, usize)>::new();
+        let new_coords = coords.iter().filter(|&&coord| board[coord] == Square::Unknown);
+        for line in new_coords.flat_map(|coord| [coord.row_num, layout.num_rows + coord.col_num]) {
+            match lines.iter_mut().find(|(existing, _)| *existing == line) {
+                Some((_, count)) => *count += 1,
+                None             => lines.push((line, 1)),
+            }
+        }
+
+        let mut blocks = coords.iter()
+            .enumerate()
+            .flat_map(|(square_idx, coord)| {
+                ship.expected_square_for_idx(square_idx)
+                    .water_neighbors()
+                    .into_iter()
+                    .filter_map(move |neighbor| coord.neighbor(neighbor))
+            })
+            .chain(coords.iter().cloned())
+            .map(to_idx)
+            .collect::<Vec<_>>();
+        blocks.sort();
+        blocks.dedup();
+
+        Candidate {
+            size: ship.size,
+            squares: coords.iter().cloned().map(to_idx).collect(),
+            blocks,
+            lines,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::parse_board;
+    use crate::solve::{all_rules, apply_rules, Quiet, SolveConfig};
+
+    // Run every rule but this one, until they get stuck
+    fn apply_other_rules(board: &mut Board) -> Result<()> {
+        let other_rules = all_rules().into_iter()
+            .filter(|&(name, _)| name != "place_fleet_in_segments")
+            .collect::<Vec<_>>();
+
+        apply_rules(board, &other_rules, &mut Quiet, &mut SolveConfig::default(), &mut 0, None)?;
+        Ok(())
+    }
+
+    fn do_test(before: Vec<&str>, after: Vec<&str>) -> Result<()> {
+        let mut board = Board::new(&before)?;
+        let expected = after.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+
+        place_fleet_in_segments(&mut board)?;
+        assert_eq!(board.to_strings(), expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_finds_open_segments() -> Result<()> {
+        let board = Board::new(&[
+            "  000",
+            "0|~  ",
+            "0|~~~",
+            "0|  ~",
+        ])?;
+
+        let segments = open_segments(&board);
+        let row_segments = segments.iter()
+            .filter(|segment| segment.axis == Axis::Row)
+            .map(|segment| (segment.index, segment.start, segment.len))
+            .collect::<Vec<_>>();
+        let col_segments = segments.iter()
+            .filter(|segment| segment.axis == Axis::Col)
+            .map(|segment| (segment.index, segment.start, segment.len))
+            .collect::<Vec<_>>();
+
+        assert_eq!(row_segments, vec![(0, 1, 2), (2, 0, 2)]);
+        assert_eq!(col_segments, vec![(0, 2, 1), (1, 0, 1), (1, 2, 1), (2, 0, 1)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_fills_squares_every_arrangement_uses() -> Result<()> {
+        let before = vec![
+            "ships: 3sq x 1, 2sq x 2, 1sq x 2.",
+            "  30321",
+            "2| ~ ~ ",
+            "1| ~   ",
+            "2| ~   ",
+            "1| ~   ",
+            "2| ~   ",
+            "1| ~~  ",
+        ];
+
+        // The other rules get stuck here
+        let mut board = Board::new(&before)?;
+        apply_other_rules(&mut board)?;
+        assert_eq!(board.to_strings(), before);
+
+        // Every way of fitting the 3 and 2 square ships into the open segments
+        // uses these three squares
+        do_test(before, vec![
+            "ships: 3sq x 1, 2sq x 2, 1sq x 2.",
+            "  20211",
+            "2| ~ ~ ",
+            "0| ~*  ",
+            "2| ~   ",
+            "0|*~   ",
+            "1| ~ * ",
+            "1| ~~  ",
+        ])
+    }
+
+    #[test]
+    fn it_makes_progress_on_a_stalled_puzzle() -> Result<()> {
+        let mut board = parse_board(include_str!("../../puzzles/generated-8x8-seed9.txt"))?;
+        let solution = parse_board(include_str!("../../puzzles/generated-8x8-seed9.solution.txt"))?;
+
+        // The other rules get stuck partway
+        apply_other_rules(&mut board)?;
+        assert!(!board.is_solved());
+
+        board.clear_dirty();
+        place_fleet_in_segments(&mut board)?;
+        assert!(board.dirty());
+
+        let layout = board.layout;
+        for change in board.changes() {
+            assert!(solution[layout.coord(change.col_num, change.row_num)].is_ship(), "{:?}", change);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_does_nothing_when_no_square_is_forced() -> Result<()> {
+        let before = vec![
+            "ships: 2sq x 2.",
+            "  1111",
+            "2|    ",
+            "0|~~~~",
+            "2|    ",
+        ];
+
+        // Either ship could be on the left
+
+        do_test(before.clone(), before)
+    }
+}
@@ -0,0 +1,400 @@
+/////////////////////////////////////////////////////////////////////
+//
+// Solve one row or col at a time, like a nonogram.
+//
+// For each row/col, try every way of filling its unknown squares with
+// ships and water that:
+// - matches the number of ship squares remaining in the row/col,
+// - agrees with the known squares. For example, a left end needs a ship to
+//   its right and water to its left,
+// - doesn't put a ship diagonally next to a ship in a neighboring row/col, and
+// - only makes runs of ship squares that could be a ship in the fleet.
+//
+// A square that's the same in every arrangement must have that value.
+//
+// The answer only depends on what the row/col looks like, so it's cached.
+// Probing asks about the same rows and cols on many hypothetical boards.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::board::*;
+use crate::error::*;
+use crate::layout::*;
+use crate::neighbor::*;
+use crate::square::*;
+
+// Start over if the cache gets bigger than this
+const MAX_CACHE_SIZE: usize = 100_000;
+
+pub fn solve_lines(board: &mut Board, row_or_col: RowOrCol) -> Result<()> {
+    let sizes = fleet_sizes(board);
+
+    let line = Line::new(board, row_or_col, &sizes);
+    let fixed = match arrangements_agree(&line) {
+        Some(fixed) => fixed,
+        None => bail!("No arrangement of ships and water fits {}", row_or_col),
+    };
+
+    for (coord, value) in row_or_col.coords().zip(fixed) {
+        if board[coord] != Square::Unknown {
+            continue;
+        }
+
+        match value {
+            Some(true)  => board.set(coord, Square::ShipSquare(ShipSquare::Any))?,
+            Some(false) => board.set(coord, Square::Water)?,
+            None        => {},
+        }
+    }
+
+    Ok(())
+}
+
+// Sizes of the ships in the fleet, smallest first. Empty if we don't know the fleet.
+fn fleet_sizes(board: &Board) -> Vec<usize> {
+    let mut sizes = board.fleet()
+        .filter(|&(_, count)| count > 0)
+        .map(|(expected_ship, _)| expected_ship.size)
+        .collect::<Vec<_>>();
+    sizes.sort();
+
+    sizes
+}
+
+/////////////////////////////////////////////////////////////////////
+//
+// A row/col, described without reference to its direction, so rows
+// and cols can share cache entries.
+
+// What a known ship square says about the squares around it
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+struct Piece {
+    before: Option<bool>, // Is the previous square in the line a ship?
+    after:  Option<bool>, // Is the next square in the line a ship?
+    along:  Option<bool>, // Does the ship run along the line, or across it?
+    middle: bool,         // If the ship runs along the line, is this square in its middle?
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+enum Cell {
+    Water,
+    Unknown {
+        diagonal_ship: bool, // A ship touches this square's corner
+        beside_ship: bool,   // A ship is next to this square, across the line
+    },
+    Ship {
+        piece: Piece,
+        beside_ship: bool,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+struct Line {
+    cells: Vec<Cell>,
+    ship_squares_remaining: usize,
+    sizes: Vec<usize>,
+}
+
+impl Line {
+    fn new(board: &Board, row_or_col: RowOrCol, sizes: &[usize]) -> Self {
+        use crate::neighbor::Neighbor::*;
+        let (sides, diagonals) = match row_or_col.axis {
+            Axis::Row => ([N, S], [NW, NE, SW, SE]),
+            Axis::Col => ([W, E], [NW, NE, SW, SE]),
+        };
+
+        let is_ship_at = |coord: Coord, neighbors: &[Neighbor]| {
+            neighbors.iter()
+                .filter_map(|&neighbor| coord.neighbor(neighbor))
+                .any(|neighbor| board[neighbor].is_ship())
+        };
+
+        let cells = row_or_col.coords()
+            .map(|coord| match board[coord] {
+                Square::Water => Cell::Water,
+                Square::Unknown => Cell::Unknown {
+                    diagonal_ship: is_ship_at(coord, &diagonals),
+                    beside_ship: is_ship_at(coord, &sides),
+                },
+                Square::ShipSquare(ship_square) => Cell::Ship {
+                    piece: Piece::new(ship_square, row_or_col.axis),
+                    beside_ship: is_ship_at(coord, &sides),
+                },
+            })
+            .collect();
+
+        Line {
+            cells,
+            ship_squares_remaining: board.ship_squares_remaining(row_or_col),
+            sizes: sizes.to_vec(),
+        }
+    }
+
+    // Is this arrangement of ships (true) and water (false) allowed?
+    fn allows(&self, is_ship: &[bool]) -> bool {
+        let is_ship_at = |idx: Option<usize>| idx
+            .and_then(|idx| is_ship.get(idx))
+            .cloned()
+            .unwrap_or(false);
+
+        // Check each run of ship squares
+        let mut idx = 0;
+        while idx < is_ship.len() {
+            if !is_ship[idx] {
+                idx += 1;
+                continue;
+            }
+
+            let start = idx;
+            while idx < is_ship.len() && is_ship[idx] {
+                idx += 1;
+            }
+            let len = idx - start;
+            let along = len >= 2;
+
+            // A run of 2+ is a whole ship, since ships can't touch
+            if along && !self.sizes.is_empty() && !self.sizes.contains(&len) {
+                return false;
+            }
+
+            for square_idx in start .. idx {
+                let before = is_ship_at(square_idx.checked_sub(1));
+                let after  = is_ship_at(Some(square_idx + 1));
+                let is_end = square_idx == start || square_idx == idx - 1;
+
+                let is_allowed = match self.cells[square_idx] {
+                    Cell::Water => false,
+                    Cell::Unknown { diagonal_ship, beside_ship } => {
+                        !(diagonal_ship || along && beside_ship)
+                    },
+                    Cell::Ship { piece, beside_ship } => {
+                        !(along && beside_ship)
+                            && piece.along.is_none_or(|piece_along| piece_along == along)
+                            && piece.before.is_none_or(|piece_before| piece_before == before)
+                            && piece.after.is_none_or(|piece_after| piece_after == after)
+                            && !(piece.middle && along && is_end)
+                    },
+                };
+
+                if !is_allowed {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+impl Piece {
+    fn new(ship_square: ShipSquare, axis: Axis) -> Self {
+        use crate::neighbor::Neighbor::*;
+
+        let (before, after) = match axis {
+            Axis::Row => (W, E),
+            Axis::Col => (N, S),
+        };
+
+        let along = match (ship_square, axis) {
+            (ShipSquare::Any, _)       |
+            (ShipSquare::AnyMiddle, _) => None,
+            (ShipSquare::Dot, _)       => Some(false),
+
+            (ShipSquare::LeftEnd, Axis::Row)          |
+            (ShipSquare::RightEnd, Axis::Row)         |
+            (ShipSquare::HorizontalMiddle, Axis::Row) |
+            (ShipSquare::TopEnd, Axis::Col)           |
+            (ShipSquare::BottomEnd, Axis::Col)        |
+            (ShipSquare::VerticalMiddle, Axis::Col)   => Some(true),
+
+            _ => Some(false),
+        };
+
+        // Any and AnyMiddle don't say anything about their neighbors in the line
+        // until we know which way they run
+        let neighbor_is_ship = |neighbor| match ship_square {
+            ShipSquare::Any | ShipSquare::AnyMiddle => None,
+            _ => Some(!ship_square.water_neighbors().contains(&neighbor)),
+        };
+
+        Piece {
+            before: neighbor_is_ship(before),
+            after: neighbor_is_ship(after),
+            along,
+            middle: ship_square == ShipSquare::AnyMiddle,
+        }
+    }
+}
+
+/////////////////////////////////////////////////////////////////////
+//
+// Enumerating arrangements
+
+thread_local! {
+    static CACHE: RefCell<HashMap<Line, Option<Vec<Option<bool>>>>> = RefCell::new(HashMap::new());
+}
+
+// For each square in the line: Some(true) if it's a ship in every allowed
+// arrangement, Some(false) if it's water in every one, None if it varies.
+// Returns None if there are no allowed arrangements.
+fn arrangements_agree(line: &Line) -> Option<Vec<Option<bool>>> {
+    if let Some(cached) = CACHE.with(|cache| cache.borrow().get(line).cloned()) {
+        return cached;
+    }
+
+    let fixed = enumerate_arrangements(line);
+
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if cache.len() >= MAX_CACHE_SIZE {
+            cache.clear();
+        }
+        cache.insert(line.clone(), fixed.clone());
+    });
+
+    fixed
+}
+
+fn enumerate_arrangements(line: &Line) -> Option<Vec<Option<bool>>> {
+    let known = line.cells.iter()
+        .map(|cell| match cell {
+            Cell::Water          => false,
+            Cell::Unknown { .. } => false,
+            Cell::Ship { .. }    => true,
+        })
+        .collect::<Vec<_>>();
+
+    let unknown_idxs = line.cells.iter()
+        .enumerate()
+        .filter(|(_, cell)| matches!(cell, Cell::Unknown { .. }))
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+
+    let mut fixed: Option<Vec<Option<bool>>> = None;
+
+    // combinations(0) doesn't yield the empty combination
+    let choices: Box<dyn Iterator<Item = Vec<&usize>>> = match line.ship_squares_remaining {
+        0 => Box::new(std::iter::once(Vec::new())),
+        k => Box::new(unknown_idxs.iter().combinations(k)),
+    };
+
+    for ship_idxs in choices {
+        let mut is_ship = known.clone();
+        for &idx in ship_idxs {
+            is_ship[idx] = true;
+        }
+
+        if !line.allows(&is_ship) {
+            continue;
+        }
+
+        fixed = Some(match fixed {
+            None        => is_ship.iter().map(|&x| Some(x)).collect(),
+            Some(fixed) => fixed.iter()
+                .zip(is_ship.iter())
+                .map(|(&prev, &curr)| if prev == Some(curr) { prev } else { None })
+                .collect(),
+        });
+    }
+
+    fixed
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solve::Rule;
+
+    fn do_test(before: Vec<&str>, after: Vec<&str>) -> Result<()> {
+        let mut board = Board::new(&before)?;
+        let expected = after.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+
+        Rule::Lines(solve_lines).apply(&mut board)?;
+        assert_eq!(board.to_strings(), expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_fills_squares_that_are_the_same_in_every_arrangement() -> Result<()> {
+        // The 3 square ship can only go in two places, and both cover the middle
+        do_test(vec![
+            "ships: 3sq x 1.",
+            "  1111",
+            "3|    ",
+            "0|~~~~",
+            "1|    ",
+        ], vec![
+            "ships: 3sq x 1.",
+            "  1001",
+            "1| ** ",
+            "0|~~~~",
+            "1| ~~ ",
+        ])
+    }
+
+    #[test]
+    fn it_follows_ends() -> Result<()> {
+        // The left end needs water to its left and a ship to its right, and
+        // the only ship is 3 squares long
+        do_test(vec![
+            "ships: 3sq x 1.",
+            "  00011",
+            "2|  <  ",
+        ], vec![
+            "ships: 3sq x 1.",
+            "  00000",
+            "0|~~<**",
+        ])
+    }
+
+    #[test]
+    fn it_keeps_ships_off_the_diagonals() -> Result<()> {
+        do_test(vec![
+            "  0100",
+            "0|~*~~",
+            "1|    ",
+        ], vec![
+            "  0000",
+            "0|~*~~",
+            "0|~*~~",
+        ])
+    }
+
+    #[test]
+    fn it_errors_when_nothing_fits() -> Result<()> {
+        let mut board = Board::new(&[
+            "ships: 2sq x 1.",
+            "  000",
+            "3|   ",
+        ])?;
+
+        assert!(Rule::Lines(solve_lines).apply(&mut board).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_caches_lines() -> Result<()> {
+        let board = Board::new(&[
+            "  11",
+            "1|  ",
+            "1|  ",
+        ])?;
+
+        let line = Line::new(&board, board.layout.row(0), &[]);
+        CACHE.with(|cache| cache.borrow_mut().remove(&line));
+
+        assert_eq!(arrangements_agree(&line), Some(vec![None, None]));
+        assert!(CACHE.with(|cache| cache.borrow().contains_key(&line)));
+
+        // Row 1 looks just like row 0
+        let other_line = Line::new(&board, board.layout.row(1), &[]);
+        assert_eq!(line, other_line);
+
+        Ok(())
+    }
+}
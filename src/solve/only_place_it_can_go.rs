@@ -30,18 +30,7 @@ pub fn find_only_place_for_ships(board: &mut Board) -> Result<()> {
 fn find_only_place_for_ship(board: &mut Board, expected_ship: ExpectedShip, num_ships: usize) -> Result<()> {
     let layout = board.layout;
     let placements = layout.possible_heads_for_ship(expected_ship)
-        .filter(|ship_head| {
-            let ship = ship_head.to_ship(expected_ship);
-
-            if let Some(num_ship_squares) = can_fit_ship_at_coord(board, ship) {
-                would_ship_at_coord_be_clear_of_other_ships(board, ship)   
-                && enough_free_ships_on_constant_axis(board, ship, num_ship_squares)
-                && enough_free_ships_on_incrementing_axis(board, ship)
-            }
-            else {
-                false
-            }
-        })
+        .filter(|ship_head| is_possible_placement(board, ship_head.to_ship(expected_ship)))
         .collect::<Vec<_>>();
 
     if placements.len() == num_ships {
@@ -79,6 +68,19 @@ fn find_only_place_for_ship(board: &mut Board, expected_ship: ExpectedShip, num_
     Ok(())
 }
 
+// Could a ship that's not yet found go here? It has to fit around the squares already on
+// the board, not touch other ships, and not need more ship squares than its rows/cols have left.
+pub fn is_possible_placement(board: &Board, ship: Ship) -> bool {
+    if let Some(num_ship_squares) = can_fit_ship_at_coord(board, ship) {
+        would_ship_at_coord_be_clear_of_other_ships(board, ship)   
+        && enough_free_ships_on_constant_axis(board, ship, num_ship_squares)
+        && enough_free_ships_on_incrementing_axis(board, ship)
+    }
+    else {
+        false
+    }
+}
+
 // After determining we can place a ship here, place it.
 fn place_ship_at_coord(board: &mut Board, ship: Ship) -> Result<()> {
     for (square_idx, coord) in ship.coords().unwrap().enumerate() {
@@ -175,45 +177,23 @@ fn can_fit_ship_at_coord(board: &Board, ship: Ship) -> Option<usize> {
 
 // Would placing a ship here cause it to touch another ship?
 // Return: FALSE if it would touch another ship, TRUE if it would not touch.
-fn would_ship_at_coord_be_clear_of_other_ships<'a>(board: &Board, ship: Ship<'a>) -> bool {
-    ship.coords()
-    .unwrap() // We've previously ensured that a ship here would be in bounds
-    .enumerate()
-    .all(|(square_idx, curr_coord)| {
-        let is_first_square  = square_idx == 0;
-        let is_last_square   = square_idx == ship.size - 1;
-        let is_middle_square = !is_first_square && !is_last_square;
-        // Note: If this square is a dot, it can be both a first and last square
-        assert!(is_first_square || is_middle_square || is_last_square);
-
-        let (first_square_neighbors, middle_square_neighbors, last_square_neighbors) =
-            match ship.head.incrementing_axis {
-                Axis::Col => ( // Horizonal ship increments by columns
-                    ShipSquare::LeftEnd.water_neighbors(),
-                    ShipSquare::HorizontalMiddle.water_neighbors(),
-                    ShipSquare::RightEnd.water_neighbors()
-                ),
-                Axis::Row => ( // Vertical ship increments by rows
-                    ShipSquare::TopEnd.water_neighbors(),
-                    ShipSquare::VerticalMiddle.water_neighbors(),
-                    ShipSquare::BottomEnd.water_neighbors()
-                )
-            };
-
-        // Find the neighbors that are relevant to check for curr_coord
-        [
-            (is_first_square,  first_square_neighbors),
-            (is_middle_square, middle_square_neighbors),
-            (is_last_square,   last_square_neighbors),
-        ].iter()
-        .filter_map(|(is_used, neighbors)|
-            // is_used means "do we want to check these neighbors"
-            if *is_used { Some(neighbors) } else { None }
-        )
-        .flatten()
-        .filter_map(|&neighbor| curr_coord.neighbor(neighbor))
-        .all(|neighbor_coord| !board[neighbor_coord].is_ship() )
-    })
+//
+// The squares that have to be water are the ring around the ship: the ship's
+// rectangle grown by one square on every side, minus the ship itself.
+fn would_ship_at_coord_be_clear_of_other_ships(board: &Board, ship: Ship) -> bool {
+    let layout = board.layout;
+    let first = ship.head.origin;
+    let last = first.offset(ship.size - 1, ship.head.incrementing_axis)
+        .unwrap(); // We've previously ensured that a ship here would be in bounds
+
+    let rows = first.row_num.saturating_sub(1) ..= (last.row_num + 1).min(layout.num_rows - 1);
+    let cols = first.col_num.saturating_sub(1) ..= (last.col_num + 1).min(layout.num_cols - 1);
+    let is_in_ship = |row_num, col_num|
+        (first.row_num ..= last.row_num).contains(&row_num) && (first.col_num ..= last.col_num).contains(&col_num);
+
+    rows.flat_map(|row_num| cols.clone().map(move |col_num| (row_num, col_num)))
+        .filter(|&(row_num, col_num)| !is_in_ship(row_num, col_num))
+        .all(|(row_num, col_num)| !board[layout.coord(col_num, row_num)].is_ship())
 }
 
 
@@ -230,7 +210,7 @@ fn partition<T: Eq + Hash + Clone>(unpartitioned: Vec<HashSet<T>>) -> Vec<HashSe
         let (mut intersecting_sets, other_sets) : (Vec<HashSet<T>>, Vec<HashSet<T>>) =
             unpartitioned_iter.partition(|curr_set|
                 // .next() == None when intersection is empty (no coords in common)
-                first_set.intersection(curr_set).next() != None
+                first_set.intersection(curr_set).next().is_some()
             );
 
         intersecting_sets.push(first_set);
@@ -369,7 +349,7 @@ mod test_only_place_it_can_go {
 
     #[test]
     fn test_enough_free_ships_on_constant_axis() -> Result<()> {
-        let board = Board::new(&vec![
+        let board = Board::new(&[
             "  0000", // deliberate: Don't have enough ships on incrementing axis
             "3|    ",
             "0|    ",
@@ -380,26 +360,26 @@ mod test_only_place_it_can_go {
         let origin = board.layout.coord(0, 0);
         let ship = Ship::new(origin, Axis::Col, 3);
         let result = enough_free_ships_on_constant_axis(&board, ship, 0);
-        assert_eq!(result, true);
+        assert!(result);
 
         // Enough space - Includes an existing ships
         let origin = board.layout.coord(0, 2);
         let ship = Ship::new(origin, Axis::Col, 3);
         let result = enough_free_ships_on_constant_axis(&board, ship, 1);
-        assert_eq!(result, true);    
+        assert!(result);    
 
         // Not enough space
         let origin = board.layout.coord(0, 0);
         let ship = Ship::new(origin, Axis::Col, 4);
         let result = enough_free_ships_on_constant_axis(&board, ship, 0);
-        assert_eq!(result, false);   
+        assert!(!result);   
 
         Ok(())
     }
 
     #[test]
     fn test_enough_free_ships_on_incrementing_axis() -> Result<()> {
-        let board = Board::new(&vec![
+        let board = Board::new(&[
             "  1110", 
             "0|    ", // deliberate: Don't have enough ships on constant axis
         ])?;
@@ -408,15 +388,15 @@ mod test_only_place_it_can_go {
         let origin = board.layout.coord(0, 0);
         let ship = Ship::new(origin, Axis::Col, 3);
         let result = enough_free_ships_on_incrementing_axis(&board, ship);
-        assert_eq!(result, true);
+        assert!(result);
 
         // Not enough space
         let origin = board.layout.coord(0, 0);
         let ship = Ship::new(origin, Axis::Col, 4);
         let result = enough_free_ships_on_incrementing_axis(&board, ship);
-        assert_eq!(result, false);
+        assert!(!result);
 
-        let board = Board::new(&vec![
+        let board = Board::new(&[
             "  1010", 
             "0| *  ", // deliberate: Don't have enough ships on constant axis
         ])?;   
@@ -425,14 +405,14 @@ mod test_only_place_it_can_go {
         let origin = board.layout.coord(0, 0);
         let ship = Ship::new(origin, Axis::Col, 3);
         let result = enough_free_ships_on_incrementing_axis(&board, ship);
-        assert_eq!(result, true);
+        assert!(result);
 
         Ok(())
     }    
 
     #[test]
     fn test_would_ship_at_coord_be_clear_of_other_ships() -> Result<()> {
-        let board = Board::new(&vec![
+        let board = Board::new(&[
             "  0000",
             "0|    ",
             "0|~ ~ ",
@@ -444,19 +424,19 @@ mod test_only_place_it_can_go {
         let coord = board.layout.coord(0, 0);
         let ship = Ship::new(coord, Axis::Col, 3);
         let result = would_ship_at_coord_be_clear_of_other_ships(&board, ship);
-        assert_eq!(result, true);
+        assert!(result);
 
         // Ship here would diagonally touch the '<' at (0, 2)
         let coord = board.layout.coord(1, 0);
         let ship = Ship::new(coord, Axis::Row, 2);
         let result = would_ship_at_coord_be_clear_of_other_ships(&board, ship);
-        assert_eq!(result, false);
+        assert!(!result);
 
         // Ship here would touch the '*' at (3, 2)
         let coord = board.layout.coord(3, 0);
         let ship = Ship::new(coord, Axis::Row, 2);
         let result = would_ship_at_coord_be_clear_of_other_ships(&board, ship);
-        assert_eq!(result, false);
+        assert!(!result);
 
         Ok(())
     }
@@ -464,7 +444,7 @@ mod test_only_place_it_can_go {
 
     #[test]
     fn test_can_fit_ship_at_coord() -> Result<()> {
-        let board = Board::new(&vec![
+        let board = Board::new(&[
             "  0000",
             "0|    ",
             "0|~ ~ ",
@@ -513,7 +493,7 @@ mod test_only_place_it_can_go {
 
     #[test]
     fn test_can_fit_ship_doesnt_count_completed_ships() -> Result<()> {
-        let board = Board::new(&vec![
+        let board = Board::new(&[
             "  1111",
             "1|<-->",
         ])?;
@@ -529,7 +509,7 @@ mod test_only_place_it_can_go {
 
     #[test]
     fn test_place_ship_at_coord() -> Result<()> {
-        let mut board = Board::new(&vec![
+        let mut board = Board::new(&[
             "  002",
             "1|   ",
             "1| ~ ",
@@ -542,7 +522,7 @@ mod test_only_place_it_can_go {
         let ship = Ship::new(origin, Axis::Row, 4);
         place_ship_at_coord(&mut board, ship)?;
 
-        let expected = vec![
+        let expected = [
             "  000",
             "0|  ^",
             "0| ~|",
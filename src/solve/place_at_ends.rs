@@ -4,32 +4,29 @@ use crate::layout::*;
 use crate::neighbor::*;
 use crate::square::*;
 
-pub fn place_ships_next_to_ends(board: &mut Board) -> Result<()> {
-	let layout = board.layout;
-    for coord in layout.all_coordinates() {
-        let neighbor = match board[coord] {
-            Square::ShipSquare(ShipSquare::TopEnd)    => Neighbor::S,
-            Square::ShipSquare(ShipSquare::BottomEnd) => Neighbor::N,
-            Square::ShipSquare(ShipSquare::LeftEnd)   => Neighbor::E,
-            Square::ShipSquare(ShipSquare::RightEnd)  => Neighbor::W,
-            _                                         => continue,
-        };
+pub fn place_ships_next_to_ends(board: &mut Board, coord: Coord) -> Result<()> {
+    let neighbor = match board[coord] {
+        Square::ShipSquare(ShipSquare::TopEnd)    => Neighbor::S,
+        Square::ShipSquare(ShipSquare::BottomEnd) => Neighbor::N,
+        Square::ShipSquare(ShipSquare::LeftEnd)   => Neighbor::E,
+        Square::ShipSquare(ShipSquare::RightEnd)  => Neighbor::W,
+        _                                         => return Ok(()),
+    };
 
-        // Convert from Option<Coord> to Result<Coord>, so we can return an error
-        // if neighbor is out of bounds. That would mean that, for example, the
-        // top end of a ship is on the last row of the board. No place to put the
-        // rest of the ship.
-        let neighbor_coord_result: Result<Coord> = coord.neighbor(neighbor)
-        	.ok_or_else(
-        		|| format!("Square {:?} at {:?} wants a neighbor to the {:?}, but no place to put it.",
-        	 		board[coord], coord, neighbor).into()
-        		);
-        let neighbor_coord = neighbor_coord_result?;
+    // Convert from Option<Coord> to Result<Coord>, so we can return an error
+    // if neighbor is out of bounds. That would mean that, for example, the
+    // top end of a ship is on the last row of the board. No place to put the
+    // rest of the ship.
+    let neighbor_coord_result: Result<Coord> = coord.neighbor(neighbor)
+    	.ok_or_else(
+    		|| format!("Square {:?} at {:?} wants a neighbor to the {:?}, but no place to put it.",
+    	 		board[coord], coord, neighbor).into()
+    		);
+    let neighbor_coord = neighbor_coord_result?;
 
-        if board[neighbor_coord] == Square::Unknown {
-        	board.set(neighbor_coord, Square::ShipSquare(ShipSquare::Any))?;
-        }
-    }        
+    if board[neighbor_coord] == Square::Unknown {
+    	board.set(neighbor_coord, Square::ShipSquare(ShipSquare::Any))?;
+    }
 
     Ok(())
 }
@@ -37,12 +34,13 @@ pub fn place_ships_next_to_ends(board: &mut Board) -> Result<()> {
 #[cfg(test)]
 mod test {
 	use super::*;
+	use crate::solve::Rule;
 
 	fn do_test(before: Vec<&str>, after: Vec<&str>) -> Result<()> {
 		let mut board = Board::new(&before)?;
 		let expected = after.iter().map(|x| x.to_string()).collect::<Vec<_>>();
 
-	    place_ships_next_to_ends(&mut board)?;
+	    Rule::Neighbors(place_ships_next_to_ends).apply(&mut board)?;
 	    assert_eq!(board.to_strings(), expected);        
 
 	    Ok(())
@@ -70,9 +68,9 @@ mod test {
 	    ];
 
 		let mut board = Board::new(&before)?;
-	    let result = place_ships_next_to_ends(&mut board);
+	    let result = Rule::Neighbors(place_ships_next_to_ends).apply(&mut board);
 
-	    assert_eq!(result.is_err(), true);
+	    assert!(result.is_err());
 
 	    Ok(())
 	}
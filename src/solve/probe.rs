@@ -0,0 +1,281 @@
+/////////////////////////////////////////////////////////////////////
+//
+// Probing: when the rules are stuck, try each unknown square both ways.
+// If assuming one value leads to a contradiction, the square must be the
+// other value.
+
+use std::fmt;
+
+use crate::board::*;
+use crate::error::*;
+use crate::layout::*;
+use crate::square::*;
+
+use super::{apply_rules, cheap_rules, Quiet, SolveConfig};
+
+// A square that probing was able to fill in, and why
+#[derive(Debug, Clone)]
+pub struct Deduction {
+    pub row_num: usize,
+    pub col_num: usize,
+
+    // What the square must be
+    pub value: Square,
+
+    // What we assumed it was, and the contradiction that assumption led to
+    pub assumption: Square,
+    pub contradiction: String,
+}
+
+impl fmt::Display for Deduction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Assuming row {}, col {} is {} leads to a contradiction: {}. So it must be {}.",
+            self.row_num, self.col_num,
+            describe(self.assumption),
+            self.contradiction,
+            describe(self.value))
+    }
+}
+
+fn describe(square: Square) -> &'static str {
+    match square {
+        Square::Unknown       => "unknown",
+        Square::Water         => "water",
+        Square::ShipSquare(_) => "a ship",
+    }
+}
+
+// Probe every unknown square, filling in the ones that can only be one value.
+//
+// `depth` is how many assumptions we stack up before giving up on finding a
+// contradiction. At depth 1, we make one assumption and run the cheap rules.
+// At depth 2, when the cheap rules get stuck we probe the hypothetical board, too.
+//
+// Errors if some square can be neither water nor a ship.
+pub fn probe(board: &mut Board, depth: usize) -> Result<Vec<Deduction>> {
+    probe_settled(board, depth, None)
+}
+
+// Like probe, for a board that none of the cheap rules changed after the given
+// stamp. Then the rules only need to look around each assumption, and around
+// the deductions made since.
+pub(super) fn probe_settled(board: &mut Board, depth: usize, settled: Option<usize>) -> Result<Vec<Deduction>> {
+    let mut deductions = Vec::new();
+    if depth == 0 {
+        return Ok(deductions);
+    }
+
+    let layout = board.layout;
+    for coord in layout.all_coordinates() {
+        // Earlier deductions may have filled this square in
+        if board[coord] != Square::Unknown {
+            continue;
+        }
+
+        let water = Square::Water;
+        let ship  = Square::ShipSquare(ShipSquare::Any);
+
+        let (value, assumption, contradiction) = match (
+            find_contradiction(board, coord, water, depth, settled),
+            find_contradiction(board, coord, ship, depth, settled)) {
+
+            (None,        None)        => continue,
+            (Some(error), None)        => (ship, water, error),
+            (None,        Some(error)) => (water, ship, error),
+            (Some(water_error), Some(ship_error)) => {
+                bail!("Square at {:?} can't be water ({}) and can't be a ship ({})",
+                    coord, water_error, ship_error)
+            },
+        };
+
+        board.set(coord, value)?;
+        deductions.push(Deduction {
+            row_num: coord.row_num,
+            col_num: coord.col_num,
+            value,
+            assumption,
+            contradiction,
+        });
+    }
+
+    Ok(deductions)
+}
+
+// On a copy of the board, assume the square at coord has the given value.
+// If that leads to a contradiction, return a description of it.
+fn find_contradiction(board: &Board, coord: Coord, assumption: Square, depth: usize, settled: Option<usize>)
+    -> Option<String> {
+
+    let mut hypothetical = board.clone();
+
+    match follow_assumption(&mut hypothetical, coord, assumption, depth, settled) {
+        Ok(())     => None,
+        Err(error) => Some(error.to_string()),
+    }
+}
+
+fn follow_assumption(board: &mut Board, coord: Coord, assumption: Square, depth: usize, settled: Option<usize>)
+    -> Result<()> {
+
+    board.set(coord, assumption)?;
+
+    let rules = cheap_rules();
+    let mut settled = settled;
+    loop {
+        apply_rules(board, &rules, &mut Quiet, &mut SolveConfig::default(), &mut 0, settled)?;
+        board.check_fleet()?;
+
+        if depth <= 1 || board.is_solved() {
+            return Ok(());
+        }
+
+        // Look one assumption deeper
+        settled = Some(board.stamp());
+        if probe_settled(board, depth - 1, settled)?.is_empty() {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn do_test(depth: usize, before: Vec<&str>, after: Vec<&str>) -> Result<Vec<Deduction>> {
+        let mut board = Board::new(&before)?;
+        let expected = after.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+
+        let deductions = probe(&mut board, depth)?;
+        assert_eq!(board.to_strings(), expected);
+
+        Ok(deductions)
+    }
+
+    #[test]
+    fn it_fills_squares_when_rules_are_stuck() -> Result<()> {
+        let before = vec![
+            "ships: 3sq x 1, 2sq x 1, 1sq x 2.",
+            "  3013",
+            "1| ~  ",
+            "2| ~  ",
+            "2| ~  ",
+            "0|~~~~",
+            "2| ~  ",
+        ];
+
+        // The rules can't make progress here
+        let mut board = Board::new(&before)?;
+        apply_rules(&mut board, &crate::solve::all_rules(), &mut Quiet, &mut SolveConfig::default(), &mut 0, None)?;
+        assert!(!board.dirty());
+
+        let deductions = do_test(1, before, vec![
+            "ships: 3sq x 1, 2sq x 1, 1sq x 2.",
+            "  0000",
+            "0|~~~*",
+            "0|*~~*",
+            "0|*~~*",
+            "0|~~~~",
+            "0|*~*~",
+        ])?;
+
+        assert_eq!(deductions[0].to_string(),
+            "Assuming row 0, col 0 is a ship leads to a contradiction: \
+            Found 2 ships of size 2, but the puzzle only has 1. So it must be water.");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_probes_deeper() -> Result<()> {
+        let before = vec![
+            "ships: 3sq x 1, 2sq x 1, 1sq x 2.",
+            "  11121",
+            "3|  *  ",
+            "0|~~~~~",
+            "2|     ",
+            "0|~~~~~",
+            "1|     ",
+        ];
+
+        // One assumption isn't enough
+        let deductions = do_test(1, before.clone(), before.clone())?;
+        assert!(deductions.is_empty());
+
+        do_test(2, before, vec![
+            "ships: 3sq x 1, 2sq x 1, 1sq x 2.",
+            "  11121",
+            "3|  *  ",
+            "0|~~~~~",
+            "2|~    ",
+            "0|~~~~~",
+            "1|     ",
+        ])?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_errors_if_square_has_no_possible_value() -> Result<()> {
+        let mut board = Board::new(&[
+            "  1",
+            "0| ",
+        ])?;
+
+        assert!(probe(&mut board, 1).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_probes_a_settled_board_the_same_way() -> Result<()> {
+        let mut board = Board::new(&[
+            "ships: 3sq x 1, 2sq x 2, 1sq x 3.",
+            "  201213",
+            "2|      ",
+            "1|  ~   ",
+            "2|      ",
+            "0|      ",
+            "2|    * ",
+            "2|      ",
+        ])?;
+        apply_rules(&mut board, &crate::solve::all_rules(), &mut Quiet, &mut SolveConfig::default(), &mut 0, None)?;
+
+        // Only looking around each assumption finds the same things as looking everywhere
+        for depth in 1 ..= 2 {
+            let mut everywhere = board.clone();
+            let mut settled = board.clone();
+            let found_everywhere = probe(&mut everywhere, depth)?;
+            let found_settled = probe_settled(&mut settled, depth, Some(board.stamp()))?;
+
+            // The contradictions can be worded differently, depending on
+            // which square the rules trip over first
+            let squares = |deductions: &[Deduction]| deductions.iter()
+                .map(|deduction| (deduction.row_num, deduction.col_num, deduction.value, deduction.assumption))
+                .collect::<Vec<_>>();
+
+            assert!(!found_everywhere.is_empty());
+            assert_eq!(settled.to_strings(), everywhere.to_strings());
+            assert_eq!(squares(&found_settled), squares(&found_everywhere));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_solves_with_probing() -> Result<()> {
+        let mut board = Board::new(&[
+            "ships: 3sq x 1, 2sq x 1, 1sq x 2.",
+            "  3013",
+            "1|    ",
+            "2|    ",
+            "2|    ",
+            "0|    ",
+            "2|    ",
+        ])?;
+
+        assert!(!crate::solve::solve(&mut board.clone(), &mut Quiet)?);
+        assert!(crate::solve::solve_with_probing(&mut board, 1, &mut Quiet)?);
+
+        Ok(())
+    }
+}
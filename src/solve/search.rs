@@ -0,0 +1,556 @@
+/////////////////////////////////////////////////////////////////////
+//
+// A complete solver, for when the rules get stuck.
+//
+// Every ship that remains to be found is a choice among its legal placements.
+// We pick placements one at a time, propagating the row/col counts and the
+// water around each ship, and backtrack when something can't be satisfied.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::board::*;
+use crate::error::*;
+use crate::ship::*;
+use crate::square::*;
+
+pub fn solve_by_search(board: &mut Board) -> Result<bool> {
+    let search = Search::new(board)?;
+
+    let solution = match search.find_solutions(1).into_iter().next() {
+        Some(solution) => solution,
+        None           => bail!("Puzzle has no solution"),
+    };
+
+    search.apply_solution(board, &solution)?;
+
+    Ok(board.is_solved())
+}
+
+// Count the board's solutions, stopping once `max_solutions` are found.
+// Asking for up to 2 is enough to tell whether a puzzle is unique.
+pub fn count_solutions(board: &Board, max_solutions: usize) -> Result<usize> {
+    let search = Search::new(board)?;
+
+    Ok(search.find_solutions(max_solutions).len())
+}
+
+// A ship at a specific location, with the squares flattened to indexes into the grid
+struct Placement {
+    size: usize,
+    squares: Vec<(usize, ShipSquare)>, // square index, expected kind of ship square
+    water: Vec<usize>,                 // squares around the ship
+    lines: Vec<usize>,                 // rows and cols the ship is in
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Unknown,
+    Water,
+    Given(ShipSquare), // a ship square that no placement covers yet
+    Placed,
+}
+
+#[derive(Clone)]
+struct State {
+    cells: Vec<Cell>,
+
+    // Ship squares still needed in each line. Rows first, then cols.
+    needed: Vec<usize>,
+
+    // Ships still to place, by size
+    remaining: HashMap<usize, usize>,
+
+    // Ships of the same size are placed in increasing placement order, so we
+    // don't explore the same set of placements in every permutation.
+    last_placed: HashMap<usize, usize>,
+
+    chosen: Vec<usize>,
+}
+
+struct Search {
+    num_rows: usize,
+    num_cols: usize,
+    placements: Vec<Placement>,
+    initial: State,
+}
+
+impl Search {
+    fn new(board: &Board) -> Result<Self> {
+        let layout = board.layout;
+        let num_rows = layout.num_rows;
+        let num_cols = layout.num_cols;
+
+        let fleet = board.fleet().collect::<Vec<_>>();
+        ensure!(!fleet.is_empty(), "Searching for a solution requires a list of ships to find");
+
+        let mut placements = Vec::new();
+        for &(expected_ship, _) in fleet.iter() {
+            for ship_head in layout.possible_heads_for_ship(expected_ship) {
+                let ship = ship_head.to_ship(expected_ship);
+                placements.push(Placement::new(ship, num_rows, num_cols));
+            }
+        }
+
+        let cells = layout.all_coordinates()
+            .map(|coord| match board[coord] {
+                Square::Unknown                 => Cell::Unknown,
+                Square::Water                   => Cell::Water,
+                Square::ShipSquare(ship_square) => Cell::Given(ship_square),
+            })
+            .collect();
+
+        let needed = layout.rows_and_cols()
+            .map(|row_or_col| board.ship_squares_remaining(row_or_col))
+            .collect();
+
+        let mut search = Search {
+            num_rows,
+            num_cols,
+            placements,
+            initial: State {
+                cells,
+                needed,
+                remaining: HashMap::new(),
+                last_placed: HashMap::new(),
+                chosen: Vec::new(),
+            },
+        };
+
+        // Ships that are already complete on the board don't need to be placed.
+        // Mark them (and the water around them) as done.
+        for (expected_ship, count) in fleet {
+            let found = search.placements.iter()
+                .enumerate()
+                .filter(|(_, placement)| placement.size == expected_ship.size)
+                .filter(|(_, placement)| placement.squares.iter()
+                    .all(|&(idx, expected)| search.initial.cells[idx] == Cell::Given(expected)))
+                .map(|(placement_idx, _)| placement_idx)
+                .collect::<Vec<_>>();
+
+            ensure!(found.len() <= count,
+                "Board contains {} ships of size {}, but expected {}", found.len(), expected_ship.size, count);
+
+            for &placement_idx in found.iter() {
+                let placement = &search.placements[placement_idx];
+                for &(idx, _) in placement.squares.iter() {
+                    search.initial.cells[idx] = Cell::Placed;
+                }
+                for &idx in placement.water.iter() {
+                    if search.initial.cells[idx] == Cell::Unknown {
+                        search.initial.cells[idx] = Cell::Water;
+                    }
+                }
+            }
+
+            search.initial.remaining.insert(expected_ship.size, count - found.len());
+        }
+
+        Ok(search)
+    }
+
+    // Find up to `max_solutions` solutions. Each solution is a list of
+    // placement indexes.
+    fn find_solutions(&self, max_solutions: usize) -> Vec<Vec<usize>> {
+        let mut solutions = Vec::new();
+        self.search(self.initial.clone(), max_solutions, &mut solutions);
+        solutions
+    }
+
+    fn search(&self, state: State, max_solutions: usize, solutions: &mut Vec<Vec<usize>>) {
+        if solutions.len() >= max_solutions {
+            return;
+        }
+
+        let all_placed = state.remaining.values().all(|&count| count == 0);
+        if all_placed {
+            let is_complete = state.needed.iter().all(|&needed| needed == 0)
+                && !state.cells.iter().any(|cell| matches!(cell, Cell::Given(_)));
+
+            if is_complete {
+                solutions.push(state.chosen);
+            }
+            return;
+        }
+
+        let options = match self.choose_branch(&state) {
+            Some(options) => options,
+            None          => return, // dead end
+        };
+
+        for placement_idx in options {
+            let next_state = self.place(&state, placement_idx);
+            self.search(next_state, max_solutions, solutions);
+
+            if solutions.len() >= max_solutions {
+                return;
+            }
+        }
+    }
+
+    // Find the size of ship with the fewest legal placements, and return those
+    // placements. Return None if the state can't lead to a solution.
+    fn choose_branch(&self, state: &State) -> Option<Vec<usize>> {
+        let mut options_by_size: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (placement_idx, placement) in self.placements.iter().enumerate() {
+            if self.is_legal(state, placement_idx) {
+                options_by_size.entry(placement.size).or_default().push(placement_idx);
+            }
+        }
+
+        // Every given ship square must still be coverable
+        let mut coverable = HashSet::new();
+        // Every line must have enough room for the squares it needs
+        let mut room = vec![HashSet::new(); state.needed.len()];
+
+        for &placement_idx in options_by_size.values().flatten() {
+            let placement = &self.placements[placement_idx];
+            for &(idx, _) in placement.squares.iter() {
+                match state.cells[idx] {
+                    Cell::Given(_) => { coverable.insert(idx); },
+                    Cell::Unknown  => {
+                        room[self.row_of(idx)].insert(idx);
+                        room[self.num_rows + self.col_of(idx)].insert(idx);
+                    },
+                    _ => {},
+                }
+            }
+        }
+
+        let givens_ok = state.cells.iter()
+            .enumerate()
+            .all(|(idx, cell)| !matches!(cell, Cell::Given(_)) || coverable.contains(&idx));
+        let room_ok = state.needed.iter()
+            .zip(room.iter())
+            .all(|(&needed, room)| room.len() >= needed);
+
+        if !givens_ok || !room_ok {
+            return None;
+        }
+
+        let mut best: Option<(usize, Vec<usize>)> = None;
+        for (&size, &count) in state.remaining.iter() {
+            if count == 0 {
+                continue;
+            }
+
+            let options = options_by_size.remove(&size).unwrap_or_default();
+            if options.len() < count {
+                return None;
+            }
+
+            // Fewest options first. On a tie, prefer the bigger ship.
+            let is_better = match best {
+                None                               => true,
+                Some((best_size, ref best_options)) => {
+                    (options.len(), std::cmp::Reverse(size)) < (best_options.len(), std::cmp::Reverse(best_size))
+                },
+            };
+            if is_better {
+                best = Some((size, options));
+            }
+        }
+
+        best.map(|(_, options)| options)
+    }
+
+    fn is_legal(&self, state: &State, placement_idx: usize) -> bool {
+        let placement = &self.placements[placement_idx];
+
+        let count = state.remaining.get(&placement.size).cloned().unwrap_or(0);
+        if count == 0 {
+            return false;
+        }
+        if let Some(&last) = state.last_placed.get(&placement.size) {
+            if placement_idx <= last {
+                return false;
+            }
+        }
+
+        let squares_ok = placement.squares.iter()
+            .all(|&(idx, expected)| match state.cells[idx] {
+                Cell::Unknown     => true,
+                Cell::Given(given) => given.can_refine_to(expected),
+                _                 => false,
+            });
+
+        let water_ok = placement.water.iter()
+            .all(|&idx| matches!(state.cells[idx], Cell::Unknown | Cell::Water));
+
+        // Only unknown squares use up a line's count. Givens are already accounted for.
+        let lines_ok = squares_ok && placement.lines.iter()
+            .all(|&line| {
+                let new_squares = placement.squares.iter()
+                    .filter(|&&(idx, _)| state.cells[idx] == Cell::Unknown)
+                    .filter(|&&(idx, _)| self.row_of(idx) == line || self.num_rows + self.col_of(idx) == line)
+                    .count();
+
+                new_squares <= state.needed[line]
+            });
+
+        squares_ok && water_ok && lines_ok
+    }
+
+    fn place(&self, state: &State, placement_idx: usize) -> State {
+        let placement = &self.placements[placement_idx];
+        let mut next = state.clone();
+
+        for &(idx, _) in placement.squares.iter() {
+            if next.cells[idx] == Cell::Unknown {
+                next.needed[self.row_of(idx)] -= 1;
+                next.needed[self.num_rows + self.col_of(idx)] -= 1;
+            }
+            next.cells[idx] = Cell::Placed;
+        }
+
+        for &idx in placement.water.iter() {
+            next.cells[idx] = Cell::Water;
+        }
+
+        *next.remaining.get_mut(&placement.size).unwrap() -= 1;
+        next.last_placed.insert(placement.size, placement_idx);
+        next.chosen.push(placement_idx);
+
+        next
+    }
+
+    // Write a solution to the board
+    fn apply_solution(&self, board: &mut Board, solution: &[usize]) -> Result<()> {
+        let layout = board.layout;
+
+        for &placement_idx in solution {
+            for &(idx, expected) in self.placements[placement_idx].squares.iter() {
+                let coord = layout.coord(self.col_of(idx), self.row_of(idx));
+                board.set(coord, Square::ShipSquare(expected))?;
+            }
+        }
+
+        for coord in layout.all_coordinates() {
+            if board[coord] == Square::Unknown {
+                board.set(coord, Square::Water)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn row_of(&self, idx: usize) -> usize {
+        idx / self.num_cols
+    }
+
+    fn col_of(&self, idx: usize) -> usize {
+        idx % self.num_cols
+    }
+}
+
+impl Placement {
+    fn new(ship: Ship, num_rows: usize, num_cols: usize) -> Self {
+        let to_idx = |row_num: usize, col_num: usize| row_num * num_cols + col_num;
+
+        let coords = ship.coords().unwrap().collect::<Vec<_>>();
+        let squares = coords.iter()
+            .enumerate()
+            .map(|(square_idx, coord)| {
+                (to_idx(coord.row_num, coord.col_num), ship.expected_square_for_idx(square_idx))
+            })
+            .collect::<Vec<_>>();
+
+        let mut water = coords.iter()
+            .enumerate()
+            .flat_map(|(square_idx, coord)| {
+                ship.expected_square_for_idx(square_idx)
+                    .water_neighbors()
+                    .into_iter()
+                    .filter_map(move |neighbor| coord.neighbor(neighbor))
+            })
+            .filter(|coord| !coords.contains(coord))
+            .map(|coord| to_idx(coord.row_num, coord.col_num))
+            .collect::<Vec<_>>();
+        water.sort();
+        water.dedup();
+
+        // Lines are numbered rows first, then cols
+        let mut lines = coords.iter()
+            .flat_map(|coord| vec![coord.row_num, num_rows + coord.col_num])
+            .collect::<Vec<_>>();
+        lines.sort();
+        lines.dedup();
+
+        Placement {
+            size: ship.size,
+            squares,
+            water,
+            lines,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn do_test(before: Vec<&str>, after: Vec<&str>) -> Result<()> {
+        let mut board = Board::new(&before)?;
+        let expected = after.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+
+        let is_solved = solve_by_search(&mut board)?;
+        assert!(is_solved);
+        assert_eq!(board.to_strings(), expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_solves_small_puzzle() -> Result<()> {
+        do_test(vec![
+            "ships: 3sq x 1, 1sq x 2.",
+            "  2111",
+            "3|    ",
+            "0|    ",
+            "2|    ",
+        ],
+        vec![
+            "ships: 3sq x 0, 1sq x 0.",
+            "  0000",
+            "0|<->~",
+            "0|~~~~",
+            "0|•~~•",
+        ])
+    }
+
+    #[test]
+    fn it_uses_givens() -> Result<()> {
+        do_test(vec![
+            "ships: 2sq x 1.",
+            "  01",
+            "1|  ",
+            "0| v",
+        ],
+        vec![
+            "ships: 2sq x 0.",
+            "  00",
+            "0|~^",
+            "0|~v",
+        ])
+    }
+
+    #[test]
+    fn it_keeps_found_ships() -> Result<()> {
+        do_test(vec![
+            "ships: 2sq x 1, 1sq x 1.",
+            "  0001",
+            "1|    ",
+            "0|<>~ ",
+        ],
+        vec![
+            "ships: 2sq x 0, 1sq x 0.",
+            "  0000",
+            "0|~~~•",
+            "0|<>~~",
+        ])
+    }
+
+    #[test]
+    fn it_errors_without_solution() -> Result<()> {
+        let mut board = Board::new(&[
+            "ships: 2sq x 1.",
+            "  22",
+            "2|  ",
+            "0|  ",
+            "2|  ",
+        ])?;
+
+        assert!(solve_by_search(&mut board).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_errors_without_fleet() -> Result<()> {
+        let mut board = Board::new(&[
+            "  1",
+            "1| ",
+        ])?;
+
+        assert!(solve_by_search(&mut board).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_counts_solutions() -> Result<()> {
+        let ambiguous = Board::new(&[
+            "ships: 1sq x 2.",
+            "  101",
+            "1|   ",
+            "0|   ",
+            "1|   ",
+        ])?;
+        assert_eq!(count_solutions(&ambiguous, 1)?, 1);
+        assert_eq!(count_solutions(&ambiguous, 5)?, 2);
+
+        let unique = Board::new(&[
+            "ships: 1sq x 2.",
+            "  100",
+            "0|  •",
+            "0|   ",
+            "1|   ",
+        ])?;
+        assert_eq!(count_solutions(&unique, 5)?, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_solves_15x15_puzzle() -> Result<()> {
+        let text = [
+            "ships: 5sq x 1, 4sq x 2, 3sq x 3, ",
+            "       2sq x 4, 1sq x 4.",
+            "  021343410141121",
+            "0|       •       ",
+            "3|               ",
+            "1|               ",
+            "0|          ☐    ",
+            "1|               ",
+            "1|               ",
+            "4|     v     ☐   ",
+            "1|               ",
+            "5|               ",
+            "0|          v    ",
+            "5|               ",
+            "4|        •      ",
+            "3|               ",
+            "0|               ",
+            "0|               ",
+        ];
+
+        let mut board = Board::new(&text)?;
+        assert!(solve_by_search(&mut board)?);
+
+        // Same answer as the rules
+        let mut rule_board = Board::new(&text)?;
+        assert!(crate::solve::solve(&mut rule_board, &mut crate::solve::Quiet)?);
+        assert_eq!(board.to_strings(), rule_board.to_strings());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_solves_after_rules_get_stuck() -> Result<()> {
+        // Two ways to place the ships. The rules can't choose, the search picks one.
+        let mut board = Board::new(&[
+            "ships: 1sq x 2.",
+            "  11",
+            "1|  ",
+            "0|  ",
+            "1|  ",
+        ])?;
+
+        assert!(crate::solve::solve_with_search(&mut board, &mut crate::solve::Quiet)?);
+        assert_eq!(board.ship_squares_remaining(board.layout.row(0)), 0);
+        assert_eq!(board.ship_squares_remaining(board.layout.col(0)), 0);
+
+        Ok(())
+    }
+}
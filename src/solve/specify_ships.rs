@@ -4,54 +4,50 @@
 
 use crate::board::*;
 use crate::error::*;
+use crate::layout::*;
 use crate::square::*;
 
-pub fn refine_any_ship_to_specific_ship(board: &mut Board) -> Result<()> {
-    let layout = board.layout;
-    for coord in layout.all_coordinates() {
-        if board[coord] != Square::ShipSquare(ShipSquare::Any) {
-            continue;
-        }
-
-        // Find the type of ship square (if any) that's the best fit for this coord
-        let best_ship_square = ShipSquare::all()
-            .filter(|&ship_square| {
-                // let all_neighbors = Neighbor::all_neighbors();
-                // let water_neighbors = ship_square.water_neighbors();
-                // // TODO: If water_neighbors is a method on ship_square, then ship_neighbors should be, too.
-                // let ship_neighbors = all_neighbors.difference(&water_neighbors);
-
-                // Check ship_neighbors. Ensure they're both ships and in-bounds.
-                // Can't use layout.coords_for_neighbors here because that filters out 
-                // neigbors that are out of bounds.
-                //
-                // Ship neighbors need to be in bounds because those squares need to be
-                // populated with ships. We don't want to set (0, 0) to the right end 
-                // of a ship -- there's nowhere for the left end to go.
-                let ship_neighbors_ok = ship_square.ship_neighbors().into_iter()
-                    .all(|neighbor| coord.neighbor(neighbor)
-                        .map_or(false, // out of bounds
-                            |neighbor_coord| board[neighbor_coord].is_ship() 
-                            )
-                        );
-
-                // Check that water neighbors are either out of bounds or set to water
-                let water_neighbors_ok = ship_square.water_neighbors().into_iter()
-                    .filter_map(|neighbor| coord.neighbor(neighbor))
-                    .all(|water_coord| board[water_coord] == Square::Water);
-
-                ship_neighbors_ok && water_neighbors_ok
-            })
-            .max_by_key(|ship_square| { 
-                // If multiple ship_types match, choose the most specific type.
-                // That's the one that sets the most surrounding squares to water.
-                // Example: If both Dot and TopEnd match, prefer Dot.
-                ship_square.water_neighbors().len() 
-            });
-
-        if let Some(ship_square) = best_ship_square {
-            board.set(coord, Square::ShipSquare(ship_square))?
-        }
+pub fn refine_any_ship_to_specific_ship(board: &mut Board, coord: Coord) -> Result<()> {
+    if board[coord] != Square::ShipSquare(ShipSquare::Any) {
+        return Ok(());
+    }
+
+    // Find the type of ship square (if any) that's the best fit for this coord
+    let best_ship_square = ShipSquare::all()
+        .filter(|&ship_square| {
+            // let all_neighbors = Neighbor::all_neighbors();
+            // let water_neighbors = ship_square.water_neighbors();
+            // // TODO: If water_neighbors is a method on ship_square, then ship_neighbors should be, too.
+            // let ship_neighbors = all_neighbors.difference(&water_neighbors);
+
+            // Check ship_neighbors. Ensure they're both ships and in-bounds.
+            // Can't use layout.coords_for_neighbors here because that filters out 
+            // neigbors that are out of bounds.
+            //
+            // Ship neighbors need to be in bounds because those squares need to be
+            // populated with ships. We don't want to set (0, 0) to the right end 
+            // of a ship -- there's nowhere for the left end to go.
+            let ship_neighbors_ok = ship_square.ship_neighbors().into_iter()
+                .all(|neighbor| coord.neighbor(neighbor)
+                    .is_some_and(|neighbor_coord| board[neighbor_coord].is_ship()) // false if out of bounds
+                    );
+
+            // Check that water neighbors are either out of bounds or set to water
+            let water_neighbors_ok = ship_square.water_neighbors().into_iter()
+                .filter_map(|neighbor| coord.neighbor(neighbor))
+                .all(|water_coord| board[water_coord] == Square::Water);
+
+            ship_neighbors_ok && water_neighbors_ok
+        })
+        .max_by_key(|ship_square| { 
+            // If multiple ship_types match, choose the most specific type.
+            // That's the one that sets the most surrounding squares to water.
+            // Example: If both Dot and TopEnd match, prefer Dot.
+            ship_square.water_neighbors().len() 
+        });
+
+    if let Some(ship_square) = best_ship_square {
+        board.set(coord, Square::ShipSquare(ship_square))?
     }
 
     Ok(())    
@@ -60,12 +56,13 @@ pub fn refine_any_ship_to_specific_ship(board: &mut Board) -> Result<()> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::solve::Rule;
 
     fn do_test(before: Vec<&str>, after: Vec<&str>) -> Result<()> {
         let mut board = Board::new(&before)?;
         let expected = after.iter().map(|x| x.to_string()).collect::<Vec<_>>();
 
-        refine_any_ship_to_specific_ship(&mut board)?;
+        Rule::Neighbors(refine_any_ship_to_specific_ship).apply(&mut board)?;
         assert_eq!(board.to_strings(), expected); 
         Ok(())
     }
@@ -4,30 +4,18 @@
 
 use crate::board::*;
 use crate::error::*;
+use crate::layout::*;
 use crate::square::*;
 
-pub fn surround_ships_with_water(board: &mut Board) -> Result<()> {
-    let layout = board.layout;
-    let coords = layout.all_coordinates()
-        .filter_map(|coord| { 
-            if let Square::ShipSquare(ship_type) = board[coord] {
-                // Return an iterator of the neighbors of coord that should be
-                // set to water.
-                let iter = ship_type.water_neighbors()
-                    .into_iter()
-                    .filter_map(move |neighbor| coord.neighbor(neighbor));
-
-                Some(iter)
-            }
-            else {
-                None
-            }
-        })
-        .flatten()
-        .collect::<Vec<_>>();
-
-    for coord in coords {
-        board.set(coord, Square::Water)?;
+pub fn surround_ships_with_water(board: &mut Board, coord: Coord) -> Result<()> {
+    if let Square::ShipSquare(ship_type) = board[coord] {
+        let water_coords = ship_type.water_neighbors()
+            .into_iter()
+            .filter_map(|neighbor| coord.neighbor(neighbor));
+
+        for water_coord in water_coords {
+            board.set(water_coord, Square::Water)?;
+        }
     }
 
     Ok(())
@@ -37,12 +25,13 @@ pub fn surround_ships_with_water(board: &mut Board) -> Result<()> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::solve::Rule;
 
     fn do_test(before: Vec<&str>, after: Vec<&str>) -> Result<()> {
         let mut board = Board::new(&before)?;
         let expected = after.iter().map(|x| x.to_string()).collect::<Vec<_>>();
 
-        surround_ships_with_water(&mut board)?;
+        Rule::Neighbors(surround_ships_with_water).apply(&mut board)?;
         assert_eq!(board.to_strings(), expected);        
 
         Ok(())
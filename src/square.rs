@@ -1,6 +1,8 @@
+use std::convert::TryFrom;
 use std::fmt;
 use std::collections::HashSet;
 
+use crate::error::*;
 use crate::neighbor::*;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -33,7 +35,7 @@ impl ShipSquare {
     }
 
     // For a given ship type, which neigbors should be set to water
-    pub fn water_neighbors(self) -> HashSet<Neighbor> {
+    pub(crate) fn water_neighbors(self) -> HashSet<Neighbor> {
         use crate::neighbor::Neighbor::*;
         match self {
             Any       => [
@@ -62,8 +64,18 @@ impl ShipSquare {
         }
     }
 
+    // Can a square that's currently `self` be refined to `refined`? This is the same
+    // rule that Board::set enforces.
+    pub fn can_refine_to(self, refined: ShipSquare) -> bool {
+        match self {
+            Any       => true,
+            AnyMiddle => Square::ShipSquare(refined).is_ship_middle(),
+            _         => self == refined,
+        }
+    }
+
     // For a given ship type, which neighbors should be set to ships
-    pub fn ship_neighbors(self) -> HashSet<Neighbor> {
+    pub(crate) fn ship_neighbors(self) -> HashSet<Neighbor> {
         let all_neighbors = Neighbor::all_neighbors();
         let water_neighbors = self.water_neighbors();
 
@@ -73,6 +85,25 @@ impl ShipSquare {
     }
 }
 
+// How to write squares as text. The Unicode style uses • for a dot and ☐
+// for any middle. The ASCII style uses o and + instead.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum TextStyle {
+    #[default]
+    Unicode,
+    Ascii,
+}
+
+impl TextStyle {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "unicode" => Ok(TextStyle::Unicode),
+            "ascii"   => Ok(TextStyle::Ascii),
+            _         => bail!("Unknown text style: {}. Expected unicode or ascii", name),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Square {
     Unknown,
@@ -83,28 +114,24 @@ use self::Square::*;
 
 impl Square {
     pub fn is_ship(self) -> bool {
-        match self {
-            ShipSquare(_) => true,
-            _             => false
-        }
+        matches!(self, ShipSquare(_))
     }
 
     pub fn is_ship_middle(self) -> bool {
-        match self {
+        matches!(self,
             ShipSquare(AnyMiddle)        |
             ShipSquare(VerticalMiddle)   |
-            ShipSquare(HorizontalMiddle) => true,
-
-            _ => false,            
-        }
+            ShipSquare(HorizontalMiddle))
     }
 
+    // Either style of text is accepted
     pub fn from_char(square_char: char) -> Option<Self> {
         match square_char {
             ' ' => Some(Unknown),
             '~' => Some(Water),
             '*' => Some(ShipSquare(Any)),
             '•' => Some(ShipSquare(Dot)),
+            'o' => Some(ShipSquare(Dot)),
             '<' => Some(ShipSquare(LeftEnd)),
             '>' => Some(ShipSquare(RightEnd)),
             '^' => Some(ShipSquare(TopEnd)),
@@ -112,40 +139,48 @@ impl Square {
             '|' => Some(ShipSquare(VerticalMiddle)),
             '-' => Some(ShipSquare(HorizontalMiddle)),
             '☐' => Some(ShipSquare(AnyMiddle)),
+            '+' => Some(ShipSquare(AnyMiddle)),
             _   => None,
         }
     }
-}
 
-impl fmt::Display for Square {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let char = match self {
+    pub fn to_char(self, style: TextStyle) -> char {
+        match self {
             Unknown => ' ',
             Water   => '~',
 
-            ShipSquare(ship_type) => match ship_type {
-                Any              => '*',
-                Dot              => '•',              
-                LeftEnd          => '<',
-                RightEnd         => '>',
-                TopEnd           => '^',
-                BottomEnd        => 'v',
-                VerticalMiddle   => '|',
-                HorizontalMiddle => '-',
-                AnyMiddle        => '☐',
+            ShipSquare(ship_type) => match (ship_type, style) {
+                (Any, _)                        => '*',
+                (Dot, TextStyle::Unicode)       => '•',
+                (Dot, TextStyle::Ascii)         => 'o',
+                (LeftEnd, _)                    => '<',
+                (RightEnd, _)                   => '>',
+                (TopEnd, _)                     => '^',
+                (BottomEnd, _)                  => 'v',
+                (VerticalMiddle, _)             => '|',
+                (HorizontalMiddle, _)           => '-',
+                (AnyMiddle, TextStyle::Unicode) => '☐',
+                (AnyMiddle, TextStyle::Ascii)   => '+',
             }
-        };
+        }
+    }
+}
+
+impl fmt::Display for Square {
+    // `{:#}` writes the ASCII version
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let style = if f.alternate() { TextStyle::Ascii } else { TextStyle::Unicode };
 
-        return write!(f, "{}", char)
+        write!(f, "{}", self.to_char(style))
     }
 }
 
-impl From<char> for Square {
-    fn from(square_char : char) -> Self {
-        match Square::from_char(square_char) {
-            Some(square) => square,
-            None         => panic!("Unknown char".to_string()),
-        }
+impl TryFrom<char> for Square {
+    type Error = Error;
+
+    fn try_from(square_char: char) -> Result<Self> {
+        Square::from_char(square_char)
+            .ok_or_else(|| format!("Unknown square: {:?}", square_char).into())
     }
 }
 
@@ -0,0 +1,172 @@
+/////////////////////////////////////////////////////////////////////
+//
+// Checking that a board makes sense, before trying to solve it.
+//
+// A board that fails these checks has no solution. The solver would find
+// that out eventually, but these checks say exactly what's wrong.
+
+use crate::board::*;
+use crate::error::*;
+use crate::square::*;
+
+pub fn validate_board(board: &Board) -> Result<()> {
+    board.check_fleet()?;
+    check_fleet_fits_clues(board)?;
+
+    // Every line needs room for the ship squares it still has to find
+    for row_or_col in board.layout.rows_and_cols() {
+        let remaining = board.ship_squares_remaining(row_or_col);
+        let num_unknown = row_or_col.coords()
+            .filter(|&coord| board[coord] == Square::Unknown)
+            .count();
+
+        ensure!(remaining <= num_unknown,
+            "{} needs {} more ship squares, but only has {} unknown squares",
+            row_or_col, remaining, num_unknown);
+    }
+
+    let rows_remaining = (0 .. board.layout.num_rows)
+        .map(|row_num| board.ship_squares_remaining(board.layout.row(row_num)))
+        .sum::<usize>();
+    let cols_remaining = (0 .. board.layout.num_cols)
+        .map(|col_num| board.ship_squares_remaining(board.layout.col(col_num)))
+        .sum::<usize>();
+    ensure!(rows_remaining == cols_remaining,
+        "The rows need {} more ship squares, but the cols need {}",
+        rows_remaining, cols_remaining);
+
+    // Ship squares that are already on the board have to fit together
+    for coord in board.layout.all_coordinates() {
+        if let Square::ShipSquare(ship_square) = board[coord] {
+            for neighbor in ship_square.water_neighbors() {
+                if let Some(neighbor_coord) = coord.neighbor(neighbor) {
+                    ensure!(!board[neighbor_coord].is_ship(),
+                        "The ship square at row {}, col {} can't be next to the ship square at row {}, col {}",
+                        coord.row_num, coord.col_num, neighbor_coord.row_num, neighbor_coord.col_num);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// The fleet has to fit on the board, and cover exactly the squares that
+// the row and col counts say are ships
+fn check_fleet_fits_clues(board: &Board) -> Result<()> {
+    let layout = board.layout;
+    let mut fleet_squares = 0;
+
+    for (expected_ship, count) in board.fleet() {
+        ensure!(count == 0 || expected_ship.size <= layout.num_rows.max(layout.num_cols),
+            "The fleet has a {} ship, which is too long for a board with {} rows and {} cols",
+            expected_ship, layout.num_rows, layout.num_cols);
+
+        fleet_squares += expected_ship.size * count;
+    }
+
+    // Boards without a ships: line don't say what the fleet is
+    if board.fleet().next().is_none() {
+        return Ok(());
+    }
+
+    let row_squares = (0 .. layout.num_rows)
+        .map(|row_num| board.ship_squares_total(layout.row(row_num)))
+        .sum::<usize>();
+    ensure!(fleet_squares == row_squares,
+        "The fleet has {} ship squares, but the row counts add up to {}", fleet_squares, row_squares);
+
+    let col_squares = (0 .. layout.num_cols)
+        .map(|col_num| board.ship_squares_total(layout.col(col_num)))
+        .sum::<usize>();
+    ensure!(fleet_squares == col_squares,
+        "The fleet has {} ship squares, but the col counts add up to {}", fleet_squares, col_squares);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn validate(text: &[&str]) -> Result<()> {
+        validate_board(&Board::new(text)?)
+    }
+
+    #[test]
+    fn it_accepts_valid_boards() -> Result<()> {
+        validate(&[
+            "ships: 2sq x 1.",
+            "  01",
+            "1|  ",
+            "0| v",
+        ])
+    }
+
+    #[test]
+    fn it_rejects_lines_without_room() {
+        let error = validate(&[
+            "  20",
+            "1|~ ",
+            "1|  ",
+        ]).unwrap_err();
+
+        assert_eq!(error.to_string(), "Col: 0 needs 2 more ship squares, but only has 1 unknown squares");
+    }
+
+    #[test]
+    fn it_rejects_mismatched_counts() {
+        assert!(validate(&[
+            "  11",
+            "1|  ",
+            "0|  ",
+        ]).is_err());
+    }
+
+    #[test]
+    fn it_rejects_touching_ships() {
+        assert!(validate(&[
+            "  00",
+            "0|* ",
+            "0| *",
+        ]).is_err());
+
+        assert!(validate(&[
+            "  000",
+            "0|<>•",
+        ]).is_err());
+    }
+
+    #[test]
+    fn it_rejects_too_many_found_ships() {
+        assert!(validate(&[
+            "ships: 1sq x 1.",
+            "  000",
+            "0|•~•",
+        ]).is_err());
+    }
+
+    #[test]
+    fn it_rejects_fleets_that_dont_match_the_counts() {
+        let error = validate(&[
+            "ships: 2sq x 1, 1sq x 1.",
+            "  01",
+            "1|  ",
+            "0| v",
+        ]).unwrap_err();
+
+        assert_eq!(error.to_string(), "The fleet has 3 ship squares, but the row counts add up to 2");
+    }
+
+    #[test]
+    fn it_rejects_ships_longer_than_the_board() {
+        let error = validate(&[
+            "ships: 3sq x 1.",
+            "  11",
+            "1|  ",
+            "1|  ",
+        ]).unwrap_err();
+
+        assert_eq!(error.to_string(), "The fleet has a 3sq ship, which is too long for a board with 2 rows and 2 cols");
+    }
+}
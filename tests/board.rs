@@ -0,0 +1,56 @@
+// Building, parsing, formatting, and validating boards through the public API
+
+use communicator::*;
+
+#[test]
+fn it_round_trips_board_text() -> Result<()> {
+    let text = [
+        "ships: 3sq x 1, 1sq x 2.",
+        "  2111",
+        "3|~   ",
+        "0|    ",
+        "2| <  ",
+    ];
+    let board = Board::new(&text)?;
+
+    assert_eq!(board.layout(), Layout { num_rows: 3, num_cols: 4 });
+    assert_eq!(board.square(0, 0), Square::Water);
+    assert_eq!(board.square(1, 2), Square::ShipSquare(ShipSquare::LeftEnd));
+    assert_eq!(board.to_strings(), text);
+
+    Ok(())
+}
+
+#[test]
+fn it_parses_text_with_a_terminator() -> Result<()> {
+    let board = parse_board("  1\n1| \n.")?;
+
+    assert_eq!(board.to_strings(), ["  1", "1| "]);
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_bad_text() {
+    assert!(Board::new(&["  12", "1| "]).is_err());
+    assert!(Board::new(&["nonsense"]).is_err());
+}
+
+#[test]
+fn it_validates_boards() -> Result<()> {
+    validate_board(&Board::new(&[
+        "ships: 2sq x 1.",
+        "  01",
+        "1|  ",
+        "0| v",
+    ])?)?;
+
+    let impossible = Board::new(&[
+        "  20",
+        "1|~ ",
+        "1|  ",
+    ])?;
+    assert!(validate_board(&impossible).is_err());
+
+    Ok(())
+}
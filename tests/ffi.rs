@@ -0,0 +1,95 @@
+// The C ABI: its generated header, and a C program that uses it
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const HEADER: &str = "ffi/communicator.h";
+
+fn crate_dir() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+// The test binary is in deps/, in the target dir
+fn target_dir() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    exe.parent().unwrap().parent().unwrap().to_path_buf()
+}
+
+// `cargo test` doesn't build the static library, so build it here. It gets a
+// target dir of its own, because the one we're running from is locked.
+fn build_static_library(out_dir: &Path) -> PathBuf {
+    let status = Command::new(env!("CARGO"))
+        .args(["build", "--lib", "--target-dir"])
+        .arg(out_dir)
+        .current_dir(crate_dir())
+        .status()
+        .expect("Unable to run cargo");
+    assert!(status.success());
+
+    out_dir.join("debug/libcommunicator.a")
+}
+
+#[test]
+fn it_has_an_up_to_date_header() {
+    let config = cbindgen::Config::from_file(crate_dir().join("cbindgen.toml")).unwrap();
+    let mut generated = Vec::new();
+    cbindgen::Builder::new()
+        .with_crate(crate_dir())
+        .with_config(config)
+        .generate()
+        .expect("Unable to generate the header")
+        .write(&mut generated);
+    let generated = String::from_utf8(generated).unwrap();
+
+    let path = crate_dir().join(HEADER);
+    if env::var_os("COMMUNICATOR_UPDATE_HEADER").is_some() {
+        fs::write(&path, &generated).unwrap();
+    }
+
+    let committed = fs::read_to_string(&path).unwrap_or_default();
+    assert!(committed == generated,
+        "{} is out of date. Rerun the tests with COMMUNICATOR_UPDATE_HEADER=1 to regenerate it.", HEADER);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn it_runs_the_c_example() {
+    let out_dir = target_dir().join("ffi-example");
+    fs::create_dir_all(&out_dir).unwrap();
+    let exe = out_dir.join("example");
+    let library = build_static_library(&out_dir);
+
+    let status = Command::new("cc")
+        .arg(crate_dir().join("ffi/example.c"))
+        .arg("-I").arg(crate_dir().join("ffi"))
+        .arg("-Wall").arg("-Werror")
+        .arg("-o").arg(&exe)
+        .arg(library)
+        .args(["-lpthread", "-ldl", "-lm"])
+        .status()
+        .expect("Unable to run cc");
+    assert!(status.success());
+
+    let mut child = Command::new(&exe)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"ships: 2sq x 1.\n  01\n1|  \n0| v\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "\
+The board is 2 x 2, with 1 ship squares left to find in row 0.
+Solved:
+ships: 2sq x 0.
+  00
+0|~^
+0|~v
+Square at row 0, col 1 is a top end: yes
+Bad boards are reported: Line 1, column 1: expected a digit, or the end of the line, but found 'n'
+");
+}
@@ -0,0 +1,59 @@
+// The two-player game through the public API
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use communicator::game::*;
+use communicator::*;
+
+#[test]
+fn it_plays_a_game_between_ais() -> Result<()> {
+    let mut rng = StdRng::seed_from_u64(1);
+    let game = play_ai_game([Difficulty::Density, Difficulty::Random], standard_layout(), &standard_fleet(), &mut rng)?;
+
+    assert!(game.is_over());
+    let winner = game.winner().unwrap();
+    assert_eq!(game.ocean(1 - winner).num_ships_afloat(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_touching_fleets() {
+    let touching = vec![
+        Placement::new(0, 0, Axis::Col, 2),
+        Placement::new(2, 1, Axis::Row, 1),
+    ];
+    let fleet = [ExpectedShip { size: 2 }, ExpectedShip { size: 1 }];
+
+    assert!(validate_fleet(&Layout { num_rows: 4, num_cols: 4 }, &fleet, &touching).is_err());
+}
+
+#[test]
+fn it_makes_puzzles_from_fleets() -> Result<()> {
+    let mut rng = StdRng::seed_from_u64(1);
+    let placements = random_placements(&standard_layout(), &standard_fleet(), &mut rng)?;
+
+    let text = generate_puzzle(standard_layout(), &placements, &[], &mut rng)?;
+    let board = Board::new(&text.iter().map(String::as_str).collect::<Vec<_>>())?;
+
+    assert_eq!(count_solutions(&board, 2)?, 1);
+
+    Ok(())
+}
+
+#[test]
+fn it_verifies_fleet_commitments() -> Result<()> {
+    let layout = Layout { num_rows: 4, num_cols: 4 };
+    let fleet = [ExpectedShip { size: 1 }];
+    let placements = [Placement::new(3, 3, Axis::Row, 1)];
+    let commitment = Commitment::new(&placements, "salt");
+
+    let honest = [ShotRecord { col_num: 3, row_num: 3, result: ShotResult::Sunk(placements[0]) }];
+    verify_fleet(commitment, "salt", &placements, layout, &fleet, &honest)?;
+
+    let lie = [ShotRecord { col_num: 3, row_num: 3, result: ShotResult::Miss }];
+    assert!(verify_fleet(commitment, "salt", &placements, layout, &fleet, &lie).is_err());
+
+    Ok(())
+}
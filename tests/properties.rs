@@ -0,0 +1,151 @@
+// Property tests: hide a random fleet on a random board, give some of its
+// squares away, and check what the solver makes of the puzzle.
+//
+// The puzzles are built from a list of candidate ships, so when a property
+// fails, proptest shrinks the list and the board down to a minimal puzzle.
+
+use std::fmt;
+
+use proptest::prelude::*;
+
+use communicator::game::{puzzle_text, validate_fleet, Placement};
+use communicator::*;
+
+#[derive(Clone)]
+struct Puzzle {
+    layout: Layout,
+    placements: Vec<Placement>,
+    reveals: Vec<(usize, usize)>,
+}
+
+impl Puzzle {
+    fn board(&self) -> Board {
+        board_from_text(puzzle_text(self.layout, &self.placements, &self.reveals))
+    }
+
+    // The puzzle with every square given away
+    fn solution(&self) -> Board {
+        let everything = (0 .. self.layout.num_rows)
+            .flat_map(|row_num| (0 .. self.layout.num_cols).map(move |col_num| (col_num, row_num)))
+            .collect::<Vec<_>>();
+
+        board_from_text(puzzle_text(self.layout, &self.placements, &everything))
+    }
+}
+
+// Show the puzzle and its solution, so a failing case can be pasted into a test
+impl fmt::Debug for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Puzzle:")?;
+        for line in self.board().to_strings() {
+            writeln!(f, "    {:?},", line)?;
+        }
+
+        writeln!(f, "Solution:")?;
+        for line in self.solution().to_strings() {
+            writeln!(f, "    {:?},", line)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn board_from_text(text: Result<Vec<String>>) -> Board {
+    let text = text.expect("Generated puzzles should have one digit counts");
+    let lines = text.iter().map(String::as_str).collect::<Vec<_>>();
+    Board::new(&lines).expect("Generated puzzles should parse")
+}
+
+// Keep each candidate ship that's on the board and doesn't touch the ships
+// kept before it
+fn place_ships(layout: Layout, candidates: &[(usize, usize, bool, usize)]) -> Vec<Placement> {
+    let mut placements = Vec::new();
+
+    for &(col_num, row_num, is_vertical, size) in candidates {
+        let axis = if is_vertical { Axis::Row } else { Axis::Col };
+
+        let mut attempt = placements.clone();
+        attempt.push(Placement::new(col_num, row_num, axis, size));
+        let fleet = attempt.iter()
+            .map(|placement| ExpectedShip { size: placement.size })
+            .collect::<Vec<_>>();
+
+        if validate_fleet(&layout, &fleet, &attempt).is_ok() {
+            placements = attempt;
+        }
+    }
+
+    placements
+}
+
+fn puzzles() -> impl Strategy<Value = Puzzle> {
+    (1 ..= 8_usize, 1 ..= 8_usize)
+        .prop_flat_map(|(num_rows, num_cols)| {
+            let max_size = num_rows.max(num_cols).min(5);
+            let candidates = prop::collection::vec(
+                (0 .. num_cols, 0 .. num_rows, any::<bool>(), 1 ..= max_size), 1 .. 12);
+            let reveals = prop::collection::vec((0 .. num_cols, 0 .. num_rows), 0 .. 6);
+
+            (Just(Layout { num_rows, num_cols }), candidates, reveals)
+        })
+        .prop_filter_map("no ship fits on the board", |(layout, candidates, reveals)| {
+            let placements = place_ships(layout, &candidates);
+            if placements.is_empty() {
+                None
+            }
+            else {
+                Some(Puzzle { layout, placements, reveals })
+            }
+        })
+}
+
+// Could the solver's square still turn out to be the hidden one?
+fn agrees(found: Square, hidden: Square) -> bool {
+    match (found, hidden) {
+        (Square::Unknown, _)                                    => true,
+        (Square::Water, Square::Water)                          => true,
+        (Square::ShipSquare(found), Square::ShipSquare(hidden)) => found.can_refine_to(hidden),
+        _                                                       => false,
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(200))]
+
+    #[test]
+    fn it_solves_consistent_puzzles_without_errors(puzzle in puzzles()) {
+        let mut board = puzzle.board();
+        let result = solve(&mut board, &mut Quiet);
+
+        prop_assert!(result.is_ok(), "{}", result.unwrap_err());
+    }
+
+    #[test]
+    fn it_never_contradicts_the_hidden_fleet(puzzle in puzzles()) {
+        let solution = puzzle.solution();
+        let mut board = puzzle.board();
+        let _ = solve(&mut board, &mut Quiet);
+
+        let layout = board.layout();
+        let everywhere = (0 .. layout.num_rows)
+            .flat_map(|row_num| (0 .. layout.num_cols).map(move |col_num| (col_num, row_num)));
+
+        for (col_num, row_num) in everywhere {
+            let found = board.square(col_num, row_num);
+            let hidden = solution.square(col_num, row_num);
+            prop_assert!(agrees(found, hidden),
+                "Row {}, col {} is {:?}, but the hidden fleet has {:?} there",
+                row_num, col_num, found, hidden);
+        }
+    }
+
+    #[test]
+    fn it_only_solves_to_the_hidden_fleet(puzzle in puzzles()) {
+        let solution = puzzle.solution();
+        let mut board = puzzle.board();
+
+        if let Ok(true) = solve(&mut board, &mut Quiet) {
+            prop_assert_eq!(board.to_strings(), solution.to_strings());
+        }
+    }
+}
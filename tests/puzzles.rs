@@ -0,0 +1,151 @@
+// Solve the reference puzzles in puzzles/, and check the answers against the
+// solutions stored next to them.
+//
+// Each puzzle says whether the rules solve it on their own, in a
+// "# solved by: rules" or "# solved by: search" comment. A rule change that
+// weakens the rules fails here. So does one that strengthens them, so that
+// the comment gets updated, and the rules can't quietly slip back later.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use communicator::*;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum SolvedBy {
+    Rules,
+    Search,
+}
+
+struct Reference {
+    name: String,
+    puzzle: Board,
+    solution: Board,
+    solved_by: SolvedBy,
+}
+
+fn read_reference(path: &Path) -> Result<Reference> {
+    let name = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("").to_string();
+    let text = fs::read_to_string(path)
+        .chain_err(|| format!("Unable to read {}", path.display()))?;
+
+    let solved_by = match text.lines().find_map(|line| line.strip_prefix("# solved by: ")) {
+        Some("rules")  => SolvedBy::Rules,
+        Some("search") => SolvedBy::Search,
+        _ => return Err(format!("{} needs a \"# solved by: rules\" or \"# solved by: search\" comment", name).into()),
+    };
+
+    let solution_path = path.with_extension("solution.txt");
+    let solution_text = fs::read_to_string(&solution_path)
+        .chain_err(|| format!("Unable to read {}", solution_path.display()))?;
+
+    Ok(Reference {
+        puzzle: parse_board(&text).chain_err(|| format!("Unable to parse {}", name))?,
+        solution: parse_board(&solution_text).chain_err(|| format!("Unable to parse the solution to {}", name))?,
+        name,
+        solved_by,
+    })
+}
+
+fn references() -> Result<Vec<Reference>> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("puzzles");
+
+    let mut paths = fs::read_dir(&dir)
+        .chain_err(|| format!("Unable to read {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<PathBuf>>>()
+        .chain_err(|| format!("Unable to read {}", dir.display()))?;
+    paths.retain(|path| {
+        let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+        file_name.ends_with(".txt") && !file_name.ends_with(".solution.txt")
+    });
+    paths.sort();
+
+    let references = paths.iter()
+        .map(|path| read_reference(path))
+        .collect::<Result<Vec<_>>>()?;
+    assert!(references.len() >= 20, "Only found {} reference puzzles in {}", references.len(), dir.display());
+
+    Ok(references)
+}
+
+// Could the square the solver found still turn out to be the one in the solution?
+fn agrees(found: Square, solution: Square) -> bool {
+    match (found, solution) {
+        (Square::Unknown, _)                                      => true,
+        (Square::ShipSquare(found), Square::ShipSquare(solution)) => found.can_refine_to(solution),
+        _                                                         => found == solution,
+    }
+}
+
+fn check_partial(reference: &Reference, board: &Board) -> Option<String> {
+    let layout = board.layout();
+
+    (0 .. layout.num_rows)
+        .flat_map(|row_num| (0 .. layout.num_cols).map(move |col_num| (col_num, row_num)))
+        .find(|&(col_num, row_num)| !agrees(board.square(col_num, row_num), reference.solution.square(col_num, row_num)))
+        .map(|(col_num, row_num)| format!("{}: row {}, col {} is {:?}, but the solution has {:?}",
+            reference.name, row_num, col_num, board.square(col_num, row_num), reference.solution.square(col_num, row_num)))
+}
+
+fn check_solved(reference: &Reference, board: &Board) -> Option<String> {
+    if board.to_strings() == reference.solution.to_strings() {
+        None
+    }
+    else {
+        Some(format!("{}: solved to\n{}\nbut the solution is\n{}", reference.name,
+            board.to_strings().join("\n"), reference.solution.to_strings().join("\n")))
+    }
+}
+
+#[test]
+fn it_solves_the_reference_puzzles_with_the_rules() -> Result<()> {
+    let mut failures = Vec::new();
+
+    for reference in references()? {
+        let mut board = reference.puzzle.clone();
+        let is_solved = match solve(&mut board, &mut Quiet) {
+            Ok(is_solved) => is_solved,
+            Err(error) => {
+                failures.push(format!("{}: {}", reference.name, error));
+                continue;
+            },
+        };
+
+        let failure = match (reference.solved_by, is_solved) {
+            (SolvedBy::Rules, true)   => check_solved(&reference, &board),
+            (SolvedBy::Rules, false)  => Some(format!("{}: the rules don't solve it any more", reference.name)),
+            (SolvedBy::Search, false) => check_partial(&reference, &board),
+            (SolvedBy::Search, true)  => Some(format!(
+                "{}: the rules solve it now, so its comment should say \"# solved by: rules\"", reference.name)),
+        };
+        failures.extend(failure);
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+
+    Ok(())
+}
+
+#[test]
+fn it_solves_the_reference_puzzles_by_searching() -> Result<()> {
+    let mut failures = Vec::new();
+
+    // Searching needs to know the fleet
+    let references = references()?.into_iter()
+        .filter(|reference| *reference.puzzle.fleet_source() != FleetSource::Unknown);
+
+    for reference in references {
+        let mut board = reference.puzzle.clone();
+        let failure = match solve_with_search(&mut board, &mut Quiet) {
+            Ok(true)   => check_solved(&reference, &board),
+            Ok(false)  => Some(format!("{}: the search didn't find the solution", reference.name)),
+            Err(error) => Some(format!("{}: {}", reference.name, error)),
+        };
+        failures.extend(failure);
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+// Solving boards through the public API
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use communicator::*;
+
+fn puzzle() -> Result<Board> {
+    Board::new(&[
+        "ships: 3sq x 1, 1sq x 2.",
+        "  2111",
+        "3|    ",
+        "0|    ",
+        "2|    ",
+    ])
+}
+
+const SOLUTION: [&str; 5] = [
+    "ships: 3sq x 0, 1sq x 0.",
+    "  0000",
+    "0|<->~",
+    "0|~~~~",
+    "0|•~~•",
+];
+
+#[test]
+fn it_solves_with_each_solver() -> Result<()> {
+    let solvers: Vec<fn(&mut Board) -> Result<bool>> = vec![
+        |board| solve(board, &mut Quiet),
+        |board| solve_with_probing(board, 1, &mut Quiet),
+        |board| solve_with_search(board, &mut Quiet),
+        solve_by_search,
+    ];
+
+    for solver in solvers {
+        let mut board = puzzle()?;
+        assert!(solver(&mut board)?);
+        assert_eq!(board.to_strings(), SOLUTION);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_counts_solutions() -> Result<()> {
+    assert_eq!(count_solutions(&puzzle()?, 2)?, 1);
+
+    Ok(())
+}
+
+#[test]
+fn it_solves_within_limits() -> Result<()> {
+    let passes_seen = Rc::new(Cell::new(0));
+    let progress = {
+        let passes_seen = passes_seen.clone();
+        move |pass: usize, _board: &Board| passes_seen.set(pass)
+    };
+
+    let config = SolveConfig {
+        max_passes: Some(100),
+        progress: Some(Box::new(progress)),
+        ..Default::default()
+    };
+    let result = solve_with_config(puzzle()?, config, &mut Quiet)?;
+
+    assert_eq!(result.outcome, SolveOutcome::Solved);
+    assert_eq!(result.board.to_strings(), SOLUTION);
+    assert_eq!(passes_seen.get(), result.passes);
+
+    let cancellation = CancellationToken::new();
+    cancellation.cancel();
+    let config = SolveConfig { cancellation: Some(cancellation), ..Default::default() };
+    let result = solve_with_config(puzzle()?, config, &mut Quiet)?;
+
+    assert_eq!(result.outcome, SolveOutcome::Cancelled);
+    assert_eq!(result.board.to_strings(), puzzle()?.to_strings());
+
+    Ok(())
+}
+
+// An observer of our own, that counts the rules that changed the board
+struct RuleCounter(usize);
+
+impl SolveObserver for RuleCounter {
+    fn on_event(&mut self, event: &SolveEvent) -> Result<()> {
+        if let SolveEvent::RuleApplied { .. } = event {
+            self.0 += 1;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn it_reports_to_observers() -> Result<()> {
+    let mut counter = RuleCounter(0);
+    assert!(solve(&mut puzzle()?, &mut counter)?);
+    assert!(counter.0 > 0);
+
+    let mut out = Vec::new();
+    assert!(solve(&mut puzzle()?, &mut JsonLines::new(&mut out))?);
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.lines().all(|line| line.starts_with("{\"event\":")));
+
+    Ok(())
+}
+
+#[test]
+fn it_errors_on_contradictions() -> Result<()> {
+    let mut board = Board::new(&[
+        "ships: 2sq x 1.",
+        "  22",
+        "2|  ",
+        "0|  ",
+        "2|  ",
+    ])?;
+
+    assert!(solve_by_search(&mut board).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn it_checks_the_fleet_before_solving() -> Result<()> {
+    let mut board = Board::new(&[
+        "ships: standard-6x6",
+        "  2111",
+        "3|    ",
+        "0|    ",
+        "2|    ",
+    ])?;
+
+    let mut counter = RuleCounter(0);
+    let error = solve(&mut board, &mut counter).unwrap_err();
+
+    assert_eq!(error.to_string(), "The fleet has 10 ship squares, but the row counts add up to 5");
+    assert_eq!(counter.0, 0);
+
+    Ok(())
+}
+
+#[test]
+fn it_runs_rules_by_name() -> Result<()> {
+    assert!(rule_names().count() > 0);
+    for name in rule_names() {
+        apply_rule(&mut puzzle()?, name)?;
+    }
+
+    // Row 1 has no ship squares left, so it's all water
+    let mut board = puzzle()?;
+    apply_rule(&mut board, "fill_with_water")?;
+    assert_eq!(board.to_strings()[3], "0|~~~~");
+
+    assert!(apply_rule(&mut puzzle()?, "guess").is_err());
+
+    Ok(())
+}